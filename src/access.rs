@@ -0,0 +1,124 @@
+//! Read-only mode: a hard switch that disables every mutating command.
+//!
+//! Enabled by `--read-only`, `LOWMAIN_READ_ONLY=1` or `"read_only": true` in the
+//! active profile. neo4rs 0.8 cannot open sessions in READ access mode, so the
+//! guard is enforced client-side: mutating commands fail before connecting and
+//! their NextAction suggestions are withheld.
+//!
+//! Raw Cypher run without `--write` goes through [`read_txn`] and [`discard`]:
+//! it executes in an explicit transaction that is never committed. The
+//! statement check in `cypher::write_reason` only turns an obvious write into
+//! an early, explained error; the rollback is what keeps a missed write out.
+
+use agcli::{CommandRequest, NextAction};
+use neo4rs::{Graph, Txn};
+use std::env;
+
+use crate::config;
+use crate::error::{AppError, map_neo4j_error};
+
+/// Commands that change the database.
+const MUTATING_COMMANDS: [&str; 6] = [
+    "lowmain node create",
+    "lowmain node merge",
+    "lowmain node update",
+    "lowmain node delete",
+    "lowmain rel create",
+    "lowmain rel delete",
+];
+
+/// Where read-only mode was switched on, or `None` when writes are allowed.
+/// Resolved like connection settings: --read-only > profile selected by name >
+/// LOWMAIN_READ_ONLY > default profile.
+fn read_only_source(req: &CommandRequest<'_>) -> Result<Option<&'static str>, AppError> {
+    if let Some(v) = req.flag("read-only") {
+        return Ok(is_truthy(v).then_some("--read-only"));
+    }
+    let active = config::active_profile(req)?;
+    let from_profile = active
+        .as_ref()
+        .and_then(|a| a.profile.read_only.map(|on| (on, a.explicit)));
+    if let Some((on, true)) = from_profile {
+        return Ok(on.then_some("the selected profile"));
+    }
+    if let Ok(v) = env::var("LOWMAIN_READ_ONLY") {
+        return Ok(is_truthy(&v).then_some("LOWMAIN_READ_ONLY"));
+    }
+    Ok(from_profile.and_then(|(on, _)| on.then_some("the default profile")))
+}
+
+/// Anything but an explicit off value counts as on, so `--read-only` and
+/// `LOWMAIN_READ_ONLY=1` both enable it and `--read-only=false` does not.
+fn is_truthy(value: &str) -> bool {
+    !matches!(value.trim().to_ascii_lowercase().as_str(), "" | "0" | "false" | "no" | "off")
+}
+
+/// Whether mutating commands are disabled for this request.
+pub fn read_only(req: &CommandRequest<'_>) -> Result<bool, AppError> {
+    Ok(read_only_source(req)?.is_some())
+}
+
+/// Fail fast when `command` would mutate the database in read-only mode.
+pub fn ensure_writable(req: &CommandRequest<'_>, command: &str) -> Result<(), AppError> {
+    match read_only_source(req)? {
+        Some(source) => Err(AppError::ReadOnlyMode {
+            reason: format!("{command} is disabled by {source}"),
+        }),
+        None => Ok(()),
+    }
+}
+
+/// Whether a suggested command would mutate the database.
+pub fn is_mutating(command: &str) -> bool {
+    MUTATING_COMMANDS
+        .iter()
+        .any(|m| command == *m || command.strip_prefix(m).is_some_and(|rest| rest.starts_with(' ')))
+        || command.split_whitespace().any(|word| word == "--write")
+}
+
+/// Drop mutating suggestions when read-only mode is on.
+pub fn filter_actions(req: &CommandRequest<'_>, actions: Vec<NextAction>) -> Result<Vec<NextAction>, AppError> {
+    if !read_only(req)? {
+        return Ok(actions);
+    }
+    Ok(actions.into_iter().filter(|a| !is_mutating(&a.command)).collect())
+}
+
+/// Open the transaction a statement runs in when `--write` was not given.
+/// End it with [`discard`], never with a commit.
+pub async fn read_txn(graph: &Graph) -> Result<Txn, AppError> {
+    graph.start_txn().await.map_err(map_neo4j_error)
+}
+
+/// Roll back a transaction opened by [`read_txn`]. A ROLLBACK the server
+/// refuses (a result left open after a truncated page) is ignored: nothing
+/// was committed, and the connection is reset before it is reused, which
+/// ends the transaction just the same.
+pub async fn discard(txn: Txn) {
+    let _ = txn.rollback().await;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn mutating_commands_are_recognized() {
+        assert!(is_mutating("lowmain node create"));
+        assert!(is_mutating("lowmain node update 42"));
+        assert!(is_mutating("lowmain node merge --label=Person"));
+        assert!(is_mutating("lowmain rel create --from=1"));
+        assert!(is_mutating("lowmain query --write"));
+        assert!(!is_mutating("lowmain node find --label=Person"));
+        assert!(!is_mutating("lowmain node createx"));
+        assert!(!is_mutating("lowmain rel find --from=1"));
+    }
+
+    #[test]
+    fn flag_values_follow_env_conventions() {
+        assert!(is_truthy("1"));
+        assert!(is_truthy("true"));
+        assert!(!is_truthy("0"));
+        assert!(!is_truthy("False"));
+    }
+}
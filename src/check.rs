@@ -0,0 +1,367 @@
+//! Schema check for `query check` and `query --check`.
+//!
+//! A query that names a label, relationship type or property key the database
+//! has never seen still compiles and quietly returns no rows. This module finds
+//! the names a statement reads and compares them with the live schema, offering
+//! the closest known name as a suggestion.
+
+use serde_json::{Value, json};
+
+use crate::cypher::{self, Token};
+
+/// What kind of schema name a reference is.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Kind {
+    Label,
+    RelationshipType,
+    PropertyKey,
+}
+
+impl Kind {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Label => "label",
+            Self::RelationshipType => "relationship_type",
+            Self::PropertyKey => "property_key",
+        }
+    }
+
+    fn noun(self) -> &'static str {
+        match self {
+            Self::Label => "label",
+            Self::RelationshipType => "relationship type",
+            Self::PropertyKey => "property key",
+        }
+    }
+
+    /// The name as written in Cypher: `:Person`, `:KNOWS` or `name`.
+    fn spelled(self, name: &str) -> String {
+        match self {
+            Self::PropertyKey => name.to_string(),
+            _ => format!(":{name}"),
+        }
+    }
+}
+
+/// Schema names used by a statement. Names in CREATE, MERGE, SET and REMOVE
+/// are `writes`: the statement may introduce them, so they are never unknown.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct References {
+    pub reads: Vec<(Kind, String)>,
+    pub writes: Vec<(Kind, String)>,
+}
+
+impl References {
+    fn add(&mut self, writing: bool, kind: Kind, name: String) {
+        let list = if writing { &mut self.writes } else { &mut self.reads };
+        if !list.iter().any(|(k, n)| *k == kind && *n == name) {
+            list.push((kind, name));
+        }
+    }
+
+    fn names(&self, kind: Kind) -> Vec<&str> {
+        self.reads.iter().filter(|(k, _)| *k == kind).map(|(_, n)| n.as_str()).collect()
+    }
+
+    /// Names read, grouped by kind.
+    pub fn to_json(&self) -> Value {
+        json!({
+            "labels": self.names(Kind::Label),
+            "relationship_types": self.names(Kind::RelationshipType),
+            "property_keys": self.names(Kind::PropertyKey),
+        })
+    }
+}
+
+/// The open bracket a token sits in.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Frame {
+    Node,
+    Relationship,
+    Map,
+    Other,
+}
+
+const READ_CLAUSES: [&str; 9] = ["MATCH", "OPTIONAL", "WHERE", "WITH", "RETURN", "UNWIND", "DELETE", "CALL", "UNION"];
+const WRITE_CLAUSES: [&str; 4] = ["CREATE", "MERGE", "SET", "REMOVE"];
+/// Keywords a node pattern can follow, as opposed to a function name.
+const PATTERN_KEYWORDS: [&str; 8] = ["MATCH", "MERGE", "CREATE", "WHERE", "AND", "OR", "XOR", "NOT"];
+
+fn name_at(tokens: &[Token], i: usize) -> Option<String> {
+    match tokens.get(i)? {
+        Token::Word(name) | Token::Quoted(name) => Some(name.clone()),
+        _ => None,
+    }
+}
+
+/// The bracket opened by `symbol` after `prev`. A parenthesis after a name is a
+/// function call; a square bracket after a dash is a relationship pattern.
+fn opened_frame(prev: Option<&Token>, symbol: char) -> Frame {
+    match (symbol, prev) {
+        ('(', Some(Token::Word(word))) if PATTERN_KEYWORDS.contains(&word.to_uppercase().as_str()) => Frame::Node,
+        ('(', Some(Token::Word(_) | Token::Quoted(_))) => Frame::Other,
+        ('(', _) => Frame::Node,
+        ('[', Some(Token::Symbol('-'))) => Frame::Relationship,
+        ('{', _) => Frame::Map,
+        _ => Frame::Other,
+    }
+}
+
+/// Variables bound to nodes and relationships by patterns such as `(n:Person)`
+/// or `-[r]->`. Only their `x.key` lookups are property keys: other variables
+/// may hold maps or temporal values (`WITH date() AS d RETURN d.year`).
+fn entity_variables(tokens: &[Token]) -> Vec<String> {
+    let mut vars = Vec::new();
+    for (i, token) in tokens.iter().enumerate() {
+        let Token::Symbol(symbol @ ('(' | '[')) = token else {
+            continue;
+        };
+        let prev = i.checked_sub(1).map(|p| &tokens[p]);
+        if opened_frame(prev, *symbol) == Frame::Other {
+            continue;
+        }
+        // `(n)`, `(n:Label`, `(n {`, `[r*`; `(a.x + 1)` is an expression.
+        if let Some(name) = name_at(tokens, i + 1)
+            && matches!(tokens.get(i + 2), Some(Token::Symbol(')' | ']' | ':' | '{' | '*')))
+            && !vars.contains(&name)
+        {
+            vars.push(name);
+        }
+    }
+    vars
+}
+
+/// Collect the labels, relationship types and property keys a statement uses.
+pub fn references(cypher: &str) -> References {
+    let tokens = cypher::tokenize(cypher);
+    let mut refs = References::default();
+    // Each open bracket with the index of the token that opened it.
+    let mut frames: Vec<(Frame, usize)> = Vec::new();
+    let mut writing = false;
+    let entities = entity_variables(&tokens);
+
+    for (i, token) in tokens.iter().enumerate() {
+        let prev = i.checked_sub(1).map(|p| &tokens[p]);
+        let top = frames.last().map(|(frame, _)| *frame);
+        match token {
+            Token::Word(word) if !cypher::is_name(&tokens, i) => {
+                let word = word.to_uppercase();
+                if WRITE_CLAUSES.contains(&word.as_str()) {
+                    writing = true;
+                } else if READ_CLAUSES.contains(&word.as_str()) {
+                    writing = false;
+                }
+            }
+            Token::Symbol(symbol @ ('(' | '[' | '{')) => frames.push((opened_frame(prev, *symbol), i)),
+            Token::Symbol(')' | ']' | '}') => {
+                frames.pop();
+            }
+            Token::Symbol(':') if top == Some(Frame::Map) => {
+                // Keys of a map inside a node or relationship pattern are properties.
+                let parent = frames.len().checked_sub(2).map(|p| frames[p].0);
+                if matches!(parent, Some(Frame::Node | Frame::Relationship))
+                    && matches!(i.checked_sub(2).map(|p| &tokens[p]), Some(Token::Symbol('{' | ',')))
+                    && let Some(key) = i.checked_sub(1).and_then(|p| name_at(&tokens, p))
+                {
+                    refs.add(writing, Kind::PropertyKey, key);
+                }
+            }
+            Token::Symbol(c @ (':' | '|' | '&')) => {
+                let Some(name) = name_at(&tokens, i + 1) else {
+                    continue;
+                };
+                match top {
+                    Some(Frame::Relationship) => refs.add(writing, Kind::RelationshipType, name),
+                    Some(Frame::Node) => refs.add(writing, Kind::Label, name),
+                    // `WHERE n:Label`; `|` elsewhere separates a list comprehension.
+                    _ if *c == ':' => refs.add(writing, Kind::Label, name),
+                    _ => {}
+                }
+            }
+            Token::Symbol('.') => {
+                let Some(key) = name_at(&tokens, i + 1) else {
+                    continue;
+                };
+                let before = i.checked_sub(2).map(|p| &tokens[p]);
+                let after = tokens.get(i + 2);
+                let property = match prev {
+                    // Map projection of a node or relationship: `n {.name, .email}`.
+                    Some(Token::Symbol('{' | ',')) => match frames.last() {
+                        Some((Frame::Map, open)) => open
+                            .checked_sub(1)
+                            .and_then(|p| name_at(&tokens, p))
+                            .is_some_and(|var| entities.contains(&var)),
+                        _ => false,
+                    },
+                    // `n.key`, but not namespaced functions such as `apoc.coll.sum(`.
+                    Some(Token::Word(var) | Token::Quoted(var)) if entities.contains(var) => {
+                        !matches!(before, Some(Token::Symbol('.'))) && !matches!(after, Some(Token::Symbol('(' | '.')))
+                    }
+                    _ => false,
+                };
+                if property {
+                    refs.add(writing, Kind::PropertyKey, key);
+                }
+            }
+            _ => {}
+        }
+    }
+    refs
+}
+
+/// A name the statement reads that the database does not know.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Unknown {
+    pub kind: Kind,
+    pub name: String,
+    pub suggestion: Option<String>,
+}
+
+impl Unknown {
+    pub fn to_json(&self) -> Value {
+        json!({ "kind": self.kind.as_str(), "name": self.name, "suggestion": self.suggestion })
+    }
+
+    /// One-line description such as `label :Persn (did you mean :Person?)`.
+    pub fn describe(&self) -> String {
+        let text = format!("{} {}", self.kind.noun(), self.kind.spelled(&self.name));
+        match &self.suggestion {
+            Some(s) => format!("{text} (did you mean {}?)", self.kind.spelled(s)),
+            None => text,
+        }
+    }
+}
+
+/// Names the database knows, from `db.labels()`, `db.relationshipTypes()` and
+/// `db.propertyKeys()`.
+#[derive(Debug, Default, Clone)]
+pub struct Known {
+    pub labels: Vec<String>,
+    pub relationship_types: Vec<String>,
+    pub property_keys: Vec<String>,
+}
+
+impl Known {
+    /// Treat the names a statement writes as known, for the statements after it.
+    pub fn learn(&mut self, refs: &References) {
+        for (kind, name) in &refs.writes {
+            let names = match kind {
+                Kind::Label => &mut self.labels,
+                Kind::RelationshipType => &mut self.relationship_types,
+                Kind::PropertyKey => &mut self.property_keys,
+            };
+            if !names.contains(name) {
+                names.push(name.clone());
+            }
+        }
+    }
+
+    fn of(&self, kind: Kind) -> &[String] {
+        match kind {
+            Kind::Label => &self.labels,
+            Kind::RelationshipType => &self.relationship_types,
+            Kind::PropertyKey => &self.property_keys,
+        }
+    }
+}
+
+/// Names read by the statement that are neither in the schema nor written by
+/// the statement itself.
+pub fn unknown(refs: &References, known: &Known) -> Vec<Unknown> {
+    refs.reads
+        .iter()
+        .filter(|(kind, name)| !known.of(*kind).contains(name) && !refs.writes.contains(&(*kind, name.clone())))
+        .map(|(kind, name)| Unknown {
+            kind: *kind,
+            name: name.clone(),
+            suggestion: suggest(name, known.of(*kind)),
+        })
+        .collect()
+}
+
+/// All unknown names as one line, for an error message.
+pub fn describe_all(unknown: &[Unknown]) -> String {
+    unknown.iter().map(Unknown::describe).collect::<Vec<_>>().join("; ")
+}
+
+/// The closest candidate: a case-insensitive match, or one within a few edits.
+pub fn suggest(name: &str, candidates: &[String]) -> Option<String> {
+    let lower = name.to_lowercase();
+    let threshold = (lower.chars().count() / 3).max(1);
+    candidates
+        .iter()
+        .map(|c| (distance(&lower, &c.to_lowercase()), c))
+        .filter(|(d, _)| *d <= threshold)
+        .min_by_key(|(d, _)| *d)
+        .map(|(_, c)| c.clone())
+}
+
+/// Edit distance counting an adjacent transposition (`nmae`) as one edit.
+fn distance(a: &str, b: &str) -> usize {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+    let mut d = vec![vec![0usize; b.len() + 1]; a.len() + 1];
+    for (i, row) in d.iter_mut().enumerate() {
+        row[0] = i;
+    }
+    d[0] = (0..=b.len()).collect();
+    for i in 1..=a.len() {
+        for j in 1..=b.len() {
+            let cost = usize::from(a[i - 1] != b[j - 1]);
+            d[i][j] = (d[i - 1][j] + 1).min(d[i][j - 1] + 1).min(d[i - 1][j - 1] + cost);
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                d[i][j] = d[i][j].min(d[i - 2][j - 2] + 1);
+            }
+        }
+    }
+    d[a.len()][b.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn reads(cypher: &str, kind: Kind) -> Vec<String> {
+        references(cypher).names(kind).into_iter().map(String::from).collect()
+    }
+
+    #[test]
+    fn finds_read_names_but_not_written_ones() {
+        let cypher = "MATCH (p:Person {name: $n})-[:KNOWS|LIKES]->(f) WHERE f:Employee AND f.age > 30 \
+                      CREATE (p)-[:MET {at: $t}]->(x:Place) SET x.visited = true \
+                      RETURN p {.email}, apoc.coll.sum([1]), count(f.dept) AS c";
+        assert_eq!(reads(cypher, Kind::Label), ["Person", "Employee"]);
+        assert_eq!(reads(cypher, Kind::RelationshipType), ["KNOWS", "LIKES"]);
+        assert_eq!(reads(cypher, Kind::PropertyKey), ["name", "age", "email", "dept"]);
+        assert_eq!(reads("UNWIND $rows AS row MATCH (p:Person) WHERE p.id = row.pid RETURN p", Kind::PropertyKey), ["id"]);
+        assert!(reads("CALL db.labels() YIELD label RETURN label", Kind::PropertyKey).is_empty());
+        assert!(reads("WITH date() AS d, {a: 1} AS m RETURN d.year, date().month, m.a, m {.b}", Kind::PropertyKey).is_empty());
+
+        let refs = references(cypher);
+        assert!(refs.writes.contains(&(Kind::Label, "Place".into())));
+        assert!(refs.writes.contains(&(Kind::RelationshipType, "MET".into())));
+        assert!(refs.writes.contains(&(Kind::PropertyKey, "visited".into())));
+    }
+
+    #[test]
+    fn reports_unknown_names_with_suggestions() {
+        let known = Known {
+            labels: vec!["Person".into(), "Movie".into()],
+            relationship_types: vec!["ACTED_IN".into()],
+            property_keys: vec!["name".into(), "title".into()],
+        };
+        let refs = references("MATCH (p:person)-[:ACTED_IM]->(m:Movie) WHERE m.titel = $t RETURN p.nmae, m.zzz");
+        let unknown = unknown(&refs, &known);
+        let described: Vec<String> = unknown.iter().map(Unknown::describe).collect();
+        assert_eq!(
+            described,
+            [
+                "label :person (did you mean :Person?)",
+                "relationship type :ACTED_IM (did you mean :ACTED_IN?)",
+                "property key titel (did you mean title?)",
+                "property key nmae (did you mean name?)",
+                "property key zzz",
+            ]
+        );
+    }
+}
@@ -0,0 +1,144 @@
+use agcli::{ActionParam, Command, CommandError, CommandOutput, CommandRequest, NextAction};
+use serde_json::{Value, json};
+
+use crate::error::AppError;
+use crate::history;
+use crate::neo4j_client;
+
+/// Parse the <n> argument of show/replay.
+fn entry_number(req: &CommandRequest<'_>, usage: &str) -> Result<usize, AppError> {
+    let raw = req.arg(0).ok_or(AppError::InvalidParams {
+        reason: format!("Missing history entry number. Usage: {usage}"),
+    })?;
+    raw.parse().map_err(|_| AppError::InvalidParams {
+        reason: format!("Invalid history entry number: {raw}"),
+    })
+}
+
+fn list_command() -> Command {
+    Command::new("list", "List recent history entries, newest first")
+        .usage("lowmain history list [--limit=<n>]")
+        .handler(|req, _ctx| {
+            Box::pin(async move {
+                let path = history::path();
+                let entries = path.as_deref().map(history::load).unwrap_or_default();
+                let limit = neo4j_client::limit(req)?;
+
+                let recent: Vec<Value> = entries
+                    .iter()
+                    .rev()
+                    .take(limit)
+                    .map(|e| {
+                        let statements = e["statements"].as_array().map_or(0, Vec::len);
+                        json!({
+                            "n": e["n"],
+                            "ts": e["ts"],
+                            "command": e["argv"]
+                                .as_array()
+                                .map(|a| a.iter().filter_map(Value::as_str).collect::<Vec<_>>().join(" ")),
+                            "cypher": e["statements"][0]["cypher"],
+                            "statements": statements,
+                            "ok": e["ok"],
+                            "code": e["code"],
+                            "rows": e["rows"],
+                            "duration_ms": e["duration_ms"],
+                        })
+                    })
+                    .collect();
+
+                let next_actions: Vec<NextAction> = recent
+                    .iter()
+                    .take(3)
+                    .filter_map(|e| e["n"].as_u64())
+                    .map(|n| NextAction::new(format!("lowmain history show {n}"), format!("Show entry {n}")))
+                    .collect();
+
+                Ok(CommandOutput::new(json!({
+                    "entries": recent,
+                    "total": entries.len(),
+                    "path": path,
+                    "enabled": path.is_some(),
+                }))
+                .next_actions(next_actions))
+            })
+        })
+}
+
+fn show_command() -> Command {
+    Command::new("show", "Show one history entry")
+        .usage("lowmain history show <n>")
+        .handler(|req, _ctx| {
+            Box::pin(async move {
+                let n = entry_number(req, "lowmain history show 12")?;
+                let entry = history::entry(n)?;
+
+                Ok(CommandOutput::new(json!({ "entry": entry }))
+                    .next_action(
+                        NextAction::new(format!("lowmain history replay {n}"), "Re-run this entry")
+                            .with_param("--dry-run", ActionParam::new().description("Preview writes and roll back")),
+                    )
+                    .next_action(NextAction::new("lowmain history list", "List history entries")))
+            })
+        })
+}
+
+fn replay_command() -> Command {
+    Command::new("replay", "Re-run a history entry")
+        .usage("lowmain history replay <n> [--dry-run] [--read-only]")
+        .handler(|req, _ctx| {
+            Box::pin(async move {
+                let n = entry_number(req, "lowmain history replay 12")?;
+                let entry = history::entry(n)?;
+                let mut args = history::replay_args(&entry)?;
+                for flag in ["dry-run", "read-only"] {
+                    if req.flag(flag).is_some() && !args.iter().any(|a| a == &format!("--{flag}")) {
+                        args.push(format!("--{flag}"));
+                    }
+                }
+
+                // The entry runs as a fresh invocation, so the current guards
+                // (--write, read-only mode) apply and it is recorded in turn.
+                let exe = std::env::current_exe().map_err(|e| AppError::QueryFailed {
+                    reason: format!("Cannot locate the lowmain executable: {e}"),
+                })?;
+                let output = std::process::Command::new(exe)
+                    .args(&args)
+                    .output()
+                    .map_err(|e| AppError::QueryFailed {
+                        reason: format!("Cannot replay entry {n}: {e}"),
+                    })?;
+                let stdout = String::from_utf8_lossy(&output.stdout);
+                let envelope: Value = stdout
+                    .lines()
+                    .rev()
+                    .find_map(|line| serde_json::from_str(line).ok())
+                    .ok_or(AppError::QueryFailed {
+                        reason: format!("Replay of entry {n} produced no result envelope"),
+                    })?;
+
+                if envelope["ok"] != json!(true) {
+                    let error = &envelope["error"];
+                    return Err(CommandError::new(
+                        format!("Replay of entry {n} failed: {}", error["message"].as_str().unwrap_or("unknown error")),
+                        error["code"].as_str().unwrap_or("QUERY_FAILED"),
+                        envelope["fix"].as_str().unwrap_or("Run `lowmain history show` to inspect the entry"),
+                    ));
+                }
+
+                Ok(CommandOutput::new(json!({
+                    "replayed": n,
+                    "argv": args,
+                    "result": envelope["result"],
+                }))
+                .next_action(NextAction::new(format!("lowmain history show {n}"), "Show the original entry")))
+            })
+        })
+}
+
+pub fn register() -> Command {
+    Command::new("history", "Inspect and replay executed queries")
+        .usage("lowmain history [list|show|replay]")
+        .subcommand(list_command())
+        .subcommand(show_command())
+        .subcommand(replay_command())
+}
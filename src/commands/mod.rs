@@ -1,5 +1,8 @@
+pub mod history;
 pub mod nodes;
 pub mod ping;
+pub mod profile;
 pub mod query;
 pub mod rels;
+pub mod saved;
 pub mod schema;
@@ -1,48 +1,127 @@
-use agcli::{ActionParam, Command, CommandOutput, NextAction};
-use serde_json::json;
+use agcli::{ActionParam, Command, CommandOutput, CommandRequest, NextAction};
+use serde_json::{Map, Value, json};
 
+use crate::access;
 use crate::convert;
 use crate::error::{AppError, map_neo4j_error};
+use crate::filter;
+use crate::history;
+use crate::listing;
+use crate::mutation;
 use crate::neo4j_client;
+use crate::paging;
+use crate::params;
+use crate::summary::Summary;
+
+use super::schema;
+
+/// Label names from a list flag such as `--label=A,B` or `--any-label=A|B`.
+fn label_list(req: &CommandRequest<'_>, flag: &str) -> Vec<String> {
+    req.flag(flag)
+        .map(|raw| {
+            raw.split([',', '|'])
+                .map(str::trim)
+                .filter(|l| !l.is_empty())
+                .map(|l| format!("`{}`", l.replace('`', "``")))
+                .collect()
+        })
+        .unwrap_or_default()
+}
 
 fn find_command() -> Command {
-    Command::new("find", "Find nodes by label and optional filters")
-        .usage("lowmain node find --label=<label> [--where=<prop=val>] [--limit=<n>]")
+    Command::new("find", "Find nodes by labels and optional filters")
+        .usage("lowmain node find [--label=<A,B>] [--any-label=<A|B>] [--not-label=<A|B>] [--where=<filter>] [--order-by=<prop[:desc]>,...] [--props=<a,b>] [--exclude-props=<a,b>] [--limit=<n>] [--page-size=<n>] [--skip=<n>] [--cursor=<token>]")
         .handler(|req, ctx| {
             Box::pin(async move {
-                let label = req.flag("label").ok_or(AppError::InvalidParams {
-                    reason: "Missing --label. Usage: lowmain node find --label=Person".into(),
-                })?;
+                let labels = label_list(req, "label");
+                let any_labels = label_list(req, "any-label");
+                let not_labels = label_list(req, "not-label");
+                let where_flag = req.flag("where");
+                // Without a label every node is scanned, so a filter is required.
+                let label_less = labels.is_empty() && any_labels.is_empty();
+                if label_less && where_flag.is_none() {
+                    return Err(AppError::InvalidParams {
+                        reason: "Pass --label, --any-label, or --where to search nodes of any label. Usage: lowmain node find --label=Person or lowmain node find --where=\"email = 'a@b.c'\"".into(),
+                    }
+                    .into());
+                }
 
-                let limit: usize = req
-                    .flag("limit")
-                    .and_then(|v| v.parse().ok())
-                    .unwrap_or(100);
+                let scope: Vec<&str> = ["label", "any-label", "not-label", "where", "order-by"]
+                    .iter()
+                    .map(|flag| req.flag(flag).unwrap_or(""))
+                    .collect();
+                let page = paging::page(req, &[&["node find"], scope.as_slice()].concat())?;
+                let order = listing::order_by(req)?;
+                let projection = listing::projection(req);
+                // Pages resume after the sort key of the last row seen.
+                let terms = listing::sort_terms("n", &order);
+
+                // Filter values are bound as parameters, never interpolated.
+                let filter = where_flag.map(filter::parse).transpose()?;
 
                 let graph = neo4j_client::from_request(req, ctx).await?;
 
-                let (cypher, q) = if let Some(where_clause) = req.flag("where") {
-                    // Parse prop=val
-                    let (prop, val) = where_clause.split_once('=').ok_or(AppError::InvalidParams {
-                        reason: "Invalid --where format. Use prop=value".into(),
-                    })?;
-                    let cypher = format!(
-                        "MATCH (n:`{label}`) WHERE n.`{prop}` = $val RETURN n LIMIT {limit}"
-                    );
-                    let q = neo4rs::query(&cypher).param("val", val);
-                    (cypher, q)
+                let mut conditions = Vec::new();
+                if !any_labels.is_empty() {
+                    let any: Vec<String> = any_labels.iter().map(|l| format!("n:{l}")).collect();
+                    conditions.push(format!("({})", any.join(" OR ")));
+                }
+                conditions.extend(not_labels.iter().map(|l| format!("NOT n:{l}")));
+                let mut q_params = serde_json::Map::new();
+                if let Some(filter) = &filter {
+                    let (predicate, filter_params) = filter.compile("n");
+                    conditions.push(format!("({predicate})"));
+                    q_params = filter_params;
+                }
+                if let Some(after) = &page.after {
+                    conditions.push(format!("({})", after.predicate(&terms, &mut q_params)?));
+                }
+                let where_str = if conditions.is_empty() {
+                    String::new()
                 } else {
-                    let cypher = format!("MATCH (n:`{label}`) RETURN n LIMIT {limit}");
-                    let q = neo4rs::query(&cypher);
-                    (cypher, q)
+                    format!(" WHERE {}", conditions.join(" AND "))
+                };
+
+                let returned = match &projection {
+                    Some(projection) => {
+                        q_params.insert("projected".into(), projection.params());
+                        projection.return_item("n", listing::Entity::Node)
+                    }
+                    None => "n".to_string(),
                 };
 
+                // One extra row tells whether another page exists.
+                let cypher = format!(
+                    "MATCH (n{}){where_str} RETURN {returned}, {} {} SKIP {} LIMIT {}",
+                    labels.iter().map(|l| format!(":{l}")).collect::<String>(),
+                    listing::sort_key_item(&terms),
+                    listing::order_clause(&terms),
+                    page.skip,
+                    page.size + 1
+                );
+                history::statement(ctx, req, &cypher, &q_params.clone().into())?;
+                let q = params::bind(neo4rs::query(&cypher), &q_params)?;
+
+                let mut summary = Summary::start(&cypher);
                 let mut result = graph.execute(q).await.map_err(map_neo4j_error)?;
+                summary.mark_available();
                 let mut nodes = Vec::new();
+                let mut has_more = false;
+                let mut last_key = None;
 
                 while let Some(row) = result.next().await.map_err(map_neo4j_error)? {
-                    if let Ok(node) = row.get::<neo4rs::Node>("n") {
-                        nodes.push(convert::node_to_json(&node));
+                    if nodes.len() == page.size {
+                        has_more = true;
+                        break;
+                    }
+                    let node = match projection {
+                        Some(_) => convert::column_to_json(&row, listing::PROJECTED_COLUMN).map(listing::Projection::flatten),
+                        None => convert::column_to_json(&row, "n"),
+                    }
+                    .ok();
+                    if let Some(node) = node {
+                        last_key = convert::column_to_json(&row, listing::SORT_KEY_COLUMN).ok();
+                        nodes.push(node);
                     }
                 }
 
@@ -63,21 +142,68 @@ fn find_command() -> Command {
                     })
                     .collect();
 
-                next_actions.push(
-                    NextAction::new(
-                        format!("lowmain node create --label={label}"),
-                        format!("Create a new {label} node"),
-                    )
-                    .with_param("--props", ActionParam::new().description("JSON properties").required(true)),
-                );
+                if let Some(label) = req.flag("label").filter(|_| labels.len() == 1) {
+                    next_actions.push(
+                        NextAction::new(
+                            format!("lowmain node create --label={label}"),
+                            format!("Create a new {label} node"),
+                        )
+                        .with_param("--props", ActionParam::new().description("JSON properties").required(true)),
+                    );
+                }
 
-                Ok(CommandOutput::new(json!({
+                // A label-less search cannot use an index; point at labels that have one.
+                let mut warnings = Vec::new();
+                if label_less && let Some(filter) = &filter {
+                    let indexes = schema::fetch_indexes(&graph, &mut summary).await?;
+                    for prop in filter.properties() {
+                        let indexed = schema::indexed_labels(&indexes, prop);
+                        let hint = match indexed.as_slice() {
+                            [] => format!("no index covers {prop}"),
+                            [label] => format!("add --label={label} to use the index on {prop}"),
+                            many => format!("add --any-label={} to use the indexes on {prop}", many.join("|")),
+                        };
+                        warnings.push(json!({
+                            "operator": "AllNodesScan",
+                            "severity": "WARNING",
+                            "detail": format!("No label given, so every node is scanned; {hint}"),
+                        }));
+                        if let Some(label) = indexed.first() {
+                            next_actions.push(
+                                NextAction::new(
+                                    format!("lowmain node find --label={label}"),
+                                    format!("Search {label} nodes using the index on {prop}"),
+                                )
+                                .with_param("--where", ActionParam::new().value(where_flag.unwrap_or_default())),
+                            );
+                        }
+                    }
+                }
+
+                let next_cursor = has_more.then(|| page.next_cursor(count, last_key.and_then(paging::Position::unique)));
+                if let Some(cursor) = &next_cursor {
+                    let mut next_page = paging::Page::next_action("lowmain node find", cursor);
+                    for flag in ["label", "any-label", "not-label", "where", "order-by", "props", "exclude-props"] {
+                        if let Some(value) = req.flag(flag) {
+                            next_page = next_page.with_param(format!("--{flag}"), ActionParam::new().value(value));
+                        }
+                    }
+                    next_actions.insert(0, next_page);
+                }
+
+                let mut output = json!({
                     "cypher": cypher,
+                    "params": q_params,
                     "nodes": nodes,
                     "count": count,
-                    "label": label,
-                }))
-                .next_actions(next_actions))
+                    "label": req.flag("label"),
+                    "page": page.to_json(has_more, next_cursor.as_deref()),
+                    "summary": summary.to_json(req)?,
+                });
+                if !warnings.is_empty() {
+                    output["warnings"] = json!(warnings);
+                }
+                Ok(CommandOutput::new(output).next_actions(access::filter_actions(req, next_actions)?))
             })
         })
 }
@@ -96,13 +222,14 @@ fn get_command() -> Command {
 
                 let graph = neo4j_client::from_request(req, ctx).await?;
 
+                let cypher = "MATCH (n) WHERE elementId(n) = toString($id) OR id(n) = $id RETURN n";
+                history::statement(ctx, req, cypher, &json!({ "id": id }))?;
+                let mut summary = Summary::start(cypher);
                 let mut result = graph
-                    .execute(
-                        neo4rs::query("MATCH (n) WHERE elementId(n) = toString($id) OR id(n) = $id RETURN n")
-                            .param("id", id),
-                    )
+                    .execute(neo4rs::query(cypher).param("id", id))
                     .await
                     .map_err(map_neo4j_error)?;
+                summary.mark_available();
 
                 let row = result
                     .next()
@@ -110,45 +237,37 @@ fn get_command() -> Command {
                     .map_err(map_neo4j_error)?
                     .ok_or(AppError::NodeNotFound { id: id_str.to_string() })?;
 
-                let node = row.get::<neo4rs::Node>("n").map_err(|e| AppError::QueryFailed {
+                let node_json = convert::column_to_json(&row, "n").map_err(|e| AppError::QueryFailed {
                     reason: e.to_string(),
                 })?;
-                let node_json = convert::node_to_json(&node);
-
-                Ok(CommandOutput::new(json!({ "node": node_json }))
-                    .next_action(
-                        NextAction::new(format!("lowmain node update {id}"), "Update this node")
-                            .with_param("--set", ActionParam::new().description("JSON properties to set").required(true)),
-                    )
-                    .next_action(
-                        NextAction::new(format!("lowmain node delete {id}"), "Delete this node"),
-                    )
-                    .next_action(
-                        NextAction::new(
-                            format!("lowmain rel find --from={id}"),
-                            "Find outgoing relationships",
-                        ),
-                    )
-                    .next_action(
-                        NextAction::new(
-                            format!("lowmain rel find --to={id}"),
-                            "Find incoming relationships",
-                        ),
-                    )
-                    .next_action(
-                        NextAction::new(format!("lowmain rel create --from={id}"), "Create relationship from this node")
-                            .with_param("--to", ActionParam::new().description("Target node ID").required(true))
-                            .with_param("--type", ActionParam::new().description("Relationship type").required(true)),
-                    ))
+
+                let next_actions = vec![
+                    NextAction::new(format!("lowmain node update {id}"), "Update this node")
+                        .with_param("--set", ActionParam::new().description("JSON properties to set").required(true)),
+                    NextAction::new(format!("lowmain node delete {id}"), "Delete this node"),
+                    NextAction::new(format!("lowmain rel find --from={id}"), "Find outgoing relationships"),
+                    NextAction::new(format!("lowmain rel find --to={id}"), "Find incoming relationships"),
+                    NextAction::new(format!("lowmain rel create --from={id}"), "Create relationship from this node")
+                        .with_param("--to", ActionParam::new().description("Target node ID").required(true))
+                        .with_param("--type", ActionParam::new().description("Relationship type").required(true)),
+                ];
+
+                Ok(CommandOutput::new(json!({
+                    "node": node_json,
+                    "summary": summary.to_json(req)?,
+                }))
+                .next_actions(access::filter_actions(req, next_actions)?))
             })
         })
 }
 
 fn create_command() -> Command {
     Command::new("create", "Create a new node")
-        .usage("lowmain node create --label=<label> --props=<json>")
+        .usage("lowmain node create --label=<label> --props=<json> [--dry-run]")
         .handler(|req, ctx| {
             Box::pin(async move {
+                access::ensure_writable(req, "lowmain node create")?;
+
                 let label = req.flag("label").ok_or(AppError::InvalidParams {
                     reason: "Missing --label. Usage: lowmain node create --label=Person --props='{\"name\":\"Alice\"}'".into(),
                 })?;
@@ -157,10 +276,7 @@ fn create_command() -> Command {
                     reason: "Missing --props. Provide a JSON object of properties".into(),
                 })?;
 
-                let props: serde_json::Map<String, serde_json::Value> =
-                    serde_json::from_str(props_str).map_err(|e| AppError::InvalidParams {
-                        reason: format!("Invalid --props JSON: {e}"),
-                    })?;
+                let props = params::parse_object(props_str, "props")?;
 
                 let graph = neo4j_client::from_request(req, ctx).await?;
 
@@ -177,43 +293,41 @@ fn create_command() -> Command {
                     format!("CREATE (n:`{label}`) SET {set_clause} RETURN n")
                 };
 
-                let mut q = neo4rs::query(&cypher);
-                for (key, val) in &props {
-                    q = match val {
-                        serde_json::Value::String(s) => q.param(key.as_str(), s.clone()),
-                        serde_json::Value::Number(n) => {
-                            if let Some(i) = n.as_i64() {
-                                q.param(key.as_str(), i)
-                            } else if let Some(f) = n.as_f64() {
-                                q.param(key.as_str(), f)
-                            } else {
-                                q.param(key.as_str(), n.to_string())
-                            }
-                        }
-                        serde_json::Value::Bool(b) => q.param(key.as_str(), *b),
-                        _ => q.param(key.as_str(), val.to_string()),
-                    };
-                }
+                let q = params::bind(neo4rs::query(&cypher), &props)?;
+                history::statement(ctx, req, &cypher, &props.clone().into())?;
 
-                let mut result = graph.execute(q).await.map_err(map_neo4j_error)?;
-                let row = result
-                    .next()
-                    .await
-                    .map_err(map_neo4j_error)?
+                let dry_run = mutation::dry_run(req);
+                let mut summary = Summary::start(&cypher);
+                let row = mutation::execute_one(&graph, q, dry_run, &mut summary)
+                    .await?
                     .ok_or(AppError::QueryFailed {
                         reason: "CREATE did not return a node".into(),
                     })?;
 
-                let node = row.get::<neo4rs::Node>("n").map_err(|e| AppError::QueryFailed {
+                let node_json = convert::column_to_json(&row, "n").map_err(|e| AppError::QueryFailed {
                     reason: e.to_string(),
                 })?;
-                let node_json = convert::node_to_json(&node);
-                let new_id = node.id();
+                let new_id = node_json["_id"].as_i64().unwrap_or_default();
 
-                Ok(CommandOutput::new(json!({
+                let outcome = json!({
                     "created": true,
                     "node": node_json,
-                }))
+                });
+                let output = CommandOutput::new(mutation::result(
+                    dry_run,
+                    outcome,
+                    summary.to_json(req)?,
+                ));
+
+                if dry_run {
+                    return Ok(output.next_action(
+                        mutation::apply_action("lowmain node create")
+                            .with_param("--label", ActionParam::new().value(label))
+                            .with_param("--props", ActionParam::new().value(props_str)),
+                    ));
+                }
+
+                Ok(output
                 .next_action(NextAction::new(
                     format!("lowmain node get {new_id}"),
                     "View created node",
@@ -231,11 +345,172 @@ fn create_command() -> Command {
         })
 }
 
+/// Property set on a node only while the merge statement that created it runs.
+const CREATED_MARK: &str = "_lm_created";
+
+fn merge_command() -> Command {
+    Command::new("merge", "Create a node unless one with the same key exists, then update it")
+        .usage("lowmain node merge --label=<label> --key=<prop[,prop]> --props=<json> [--on-create=<json>] [--on-match=<json>] [--dry-run]")
+        .handler(|req, ctx| {
+            Box::pin(async move {
+                access::ensure_writable(req, "lowmain node merge")?;
+
+                let usage = "Usage: lowmain node merge --label=Person --key=email --props='{\"email\":\"a@b.c\",\"name\":\"Al\"}'";
+                let label = req.flag("label").ok_or(AppError::InvalidParams {
+                    reason: format!("Missing --label. {usage}"),
+                })?;
+                let key_flag = req.flag("key").ok_or(AppError::InvalidParams {
+                    reason: format!("Missing --key. {usage}"),
+                })?;
+                let props_str = req.flag("props").ok_or(AppError::InvalidParams {
+                    reason: format!("Missing --props. {usage}"),
+                })?;
+
+                let mut props = params::parse_object(props_str, "props")?;
+                let on_create = match req.flag("on-create") {
+                    Some(raw) => params::parse_object(raw, "on-create")?,
+                    None => serde_json::Map::new(),
+                };
+                let on_match = match req.flag("on-match") {
+                    Some(raw) => params::parse_object(raw, "on-match")?,
+                    None => serde_json::Map::new(),
+                };
+
+                // Key values come out of --props; MERGE cannot match on null.
+                let keys: Vec<&str> = key_flag.split(',').map(str::trim).filter(|k| !k.is_empty()).collect();
+                let mut key = serde_json::Map::new();
+                for k in &keys {
+                    match props.remove(*k) {
+                        Some(value) if !value.is_null() => {
+                            key.insert(k.to_string(), value);
+                        }
+                        _ => {
+                            return Err(AppError::InvalidParams {
+                                reason: format!("--props must give a non-null value for key {k}. {usage}"),
+                            }
+                            .into());
+                        }
+                    }
+                }
+                if key.is_empty() {
+                    return Err(AppError::InvalidParams {
+                        reason: format!("Empty --key. {usage}"),
+                    }
+                    .into());
+                }
+
+                let graph = neo4j_client::from_request(req, ctx).await?;
+
+                let quoted = label.replace('`', "``");
+                let key_pattern = keys
+                    .iter()
+                    .map(|k| format!("`{}`: $key.`{}`", k.replace('`', "``"), k.replace('`', "``")))
+                    .collect::<Vec<_>>()
+                    .join(", ");
+                // ON CREATE marks the node it creates, so the same statement can tell
+                // created from matched; the mark is removed before it returns.
+                let cypher = format!(
+                    "MERGE (n:`{quoted}` {{{key_pattern}}}) \
+                     ON CREATE SET n += $on_create, n.`{CREATED_MARK}` = true ON MATCH SET n += $on_match SET n += $props \
+                     WITH n, n.`{CREATED_MARK}` IS NOT NULL AS created REMOVE n.`{CREATED_MARK}` \
+                     RETURN n, created"
+                );
+                let mut merge_params = serde_json::Map::new();
+                merge_params.insert("key".into(), key.clone().into());
+                merge_params.insert("props".into(), props.clone().into());
+                merge_params.insert("on_create".into(), on_create.clone().into());
+                merge_params.insert("on_match".into(), on_match.clone().into());
+                let q = params::bind(neo4rs::query(&cypher), &merge_params)?;
+                history::statement(ctx, req, &cypher, &merge_params.into())?;
+
+                let dry_run = mutation::dry_run(req);
+                let mut summary = Summary::start(&cypher);
+                // MERGE updates every node that matches; several mean the key is not
+                // unique, and the change is rolled back rather than applied to all.
+                let too_many = |count| AppError::MergeKeyNotUnique {
+                    label: label.to_string(),
+                    key: Value::Object(key.clone()).to_string(),
+                    count,
+                };
+                let row = mutation::execute_single(&graph, q, dry_run, &mut summary, too_many)
+                    .await?
+                    .ok_or(AppError::QueryFailed {
+                        reason: "MERGE did not return a node".into(),
+                    })?;
+
+                let node_json = convert::column_to_json(&row, "n").map_err(|e| AppError::QueryFailed {
+                    reason: e.to_string(),
+                })?;
+                let created: bool = row.get("created").unwrap_or(false);
+                let node_id = node_json["_id"].as_i64().unwrap_or_default();
+
+                let constraints = schema::fetch_constraints(&graph, &mut summary).await?;
+                let constrained = schema::has_unique_constraint(&constraints, label, &keys);
+                let mut warnings = Vec::new();
+                if !constrained {
+                    warnings.push(json!({
+                        "severity": "WARNING",
+                        "detail": format!(
+                            "No uniqueness constraint on :{label}({}); concurrent merges can still create duplicates",
+                            keys.join(", ")
+                        ),
+                    }));
+                }
+
+                let mut outcome = json!({
+                    "created": created,
+                    "matched": !created,
+                    "key": key,
+                    "node": node_json,
+                });
+                if !warnings.is_empty() {
+                    outcome["warnings"] = json!(warnings);
+                }
+                let output = CommandOutput::new(mutation::result(
+                    dry_run,
+                    outcome,
+                    summary.to_json(req)?,
+                ));
+
+                if dry_run {
+                    let mut apply = mutation::apply_action("lowmain node merge")
+                        .with_param("--label", ActionParam::new().value(label))
+                        .with_param("--key", ActionParam::new().value(key_flag))
+                        .with_param("--props", ActionParam::new().value(props_str));
+                    for flag in ["on-create", "on-match"] {
+                        if let Some(value) = req.flag(flag) {
+                            apply = apply.with_param(format!("--{flag}"), ActionParam::new().value(value));
+                        }
+                    }
+                    return Ok(output.next_action(apply));
+                }
+
+                let mut next_actions = vec![NextAction::new(format!("lowmain node get {node_id}"), "View merged node")];
+                if !constrained {
+                    let props = keys
+                        .iter()
+                        .map(|k| format!("n.`{}`", k.replace('`', "``")))
+                        .collect::<Vec<_>>()
+                        .join(", ");
+                    let props = if keys.len() > 1 { format!("({props})") } else { props };
+                    let constraint = format!("CREATE CONSTRAINT FOR (n:`{quoted}`) REQUIRE {props} IS UNIQUE");
+                    next_actions.push(
+                        NextAction::new("lowmain query --write", format!("Add a uniqueness constraint on :{label}({})", keys.join(", ")))
+                            .with_param("cypher", ActionParam::new().value(constraint)),
+                    );
+                }
+                Ok(output.next_actions(access::filter_actions(req, next_actions)?))
+            })
+        })
+}
+
 fn update_command() -> Command {
-    Command::new("update", "Update a node's properties")
-        .usage("lowmain node update <id> --set=<json>")
+    Command::new("update", "Update a node's properties and labels")
+        .usage("lowmain node update <id> [--set=<json>] [--replace] [--unset=<a,b>] [--add-label=<A,B>] [--remove-label=<A,B>] [--dry-run]")
         .handler(|req, ctx| {
             Box::pin(async move {
+                access::ensure_writable(req, "lowmain node update")?;
+
                 let id_str = req.arg(0).ok_or(AppError::InvalidParams {
                     reason: "Missing node ID. Usage: lowmain node update <id> --set='{\"name\":\"Bob\"}'".into(),
                 })?;
@@ -243,59 +518,123 @@ fn update_command() -> Command {
                     reason: format!("Invalid node ID: {id_str}"),
                 })?;
 
-                let set_str = req.flag("set").ok_or(AppError::InvalidParams {
-                    reason: "Missing --set. Provide a JSON object of properties to update".into(),
-                })?;
+                let set_str = req.flag("set");
+                let replace = req.flag("replace").is_some();
+                let mut unset: Vec<String> = req
+                    .flag("unset")
+                    .map(|raw| raw.split(',').map(str::trim).filter(|k| !k.is_empty()).map(String::from).collect())
+                    .unwrap_or_default();
+                let add_labels = label_list(req, "add-label");
+                let remove_labels = label_list(req, "remove-label");
+
+                if replace && set_str.is_none() {
+                    return Err(AppError::InvalidParams {
+                        reason: "--replace needs --set with the node's new properties (--set='{}' clears them)".into(),
+                    }
+                    .into());
+                }
+                if set_str.is_none() && unset.is_empty() && add_labels.is_empty() && remove_labels.is_empty() {
+                    return Err(AppError::InvalidParams {
+                        reason: "Nothing to update. Give --set, --unset, --add-label or --remove-label".into(),
+                    }
+                    .into());
+                }
 
-                let props: serde_json::Map<String, serde_json::Value> =
-                    serde_json::from_str(set_str).map_err(|e| AppError::InvalidParams {
-                        reason: format!("Invalid --set JSON: {e}"),
-                    })?;
+                // JSON merge patch: a null in --set removes the property.
+                let mut props = set_str.map(|s| params::parse_object(s, "set")).transpose()?.unwrap_or_default();
+                if let Some(key) = unset.iter().find(|k| props.get(*k).is_some_and(|v| !v.is_null())) {
+                    return Err(AppError::InvalidParams {
+                        reason: format!("Property {key} is both set and unset. Drop it from --set or --unset"),
+                    }
+                    .into());
+                }
+                for (key, _) in props.iter().filter(|(_, v)| v.is_null()) {
+                    if !unset.contains(key) {
+                        unset.push(key.clone());
+                    }
+                }
+                props.retain(|_, v| !v.is_null());
 
                 let graph = neo4j_client::from_request(req, ctx).await?;
 
-                let set_clause: String = props
-                    .keys()
-                    .map(|k| format!("n.`{k}` = $`{k}`"))
-                    .collect::<Vec<_>>()
-                    .join(", ");
-
-                let cypher = format!("MATCH (n) WHERE id(n) = $id SET {set_clause} RETURN n");
-                let mut q = neo4rs::query(&cypher).param("id", id);
-
-                for (key, val) in &props {
-                    q = match val {
-                        serde_json::Value::String(s) => q.param(key.as_str(), s.clone()),
-                        serde_json::Value::Number(n) => {
-                            if let Some(i) = n.as_i64() {
-                                q.param(key.as_str(), i)
-                            } else if let Some(f) = n.as_f64() {
-                                q.param(key.as_str(), f)
-                            } else {
-                                q.param(key.as_str(), n.to_string())
-                            }
-                        }
-                        serde_json::Value::Bool(b) => q.param(key.as_str(), *b),
-                        _ => q.param(key.as_str(), val.to_string()),
-                    };
+                let mut set_items = Vec::new();
+                if set_str.is_some() {
+                    set_items.push(if replace { "n = $set" } else { "n += $set" }.to_string());
+                }
+                if !add_labels.is_empty() {
+                    set_items.push(format!("n:{}", add_labels.join(":")));
+                }
+                let mut remove_items: Vec<String> =
+                    unset.iter().map(|k| format!("n.`{}`", k.replace('`', "``"))).collect();
+                if !remove_labels.is_empty() {
+                    remove_items.push(format!("n:{}", remove_labels.join(":")));
                 }
 
-                let mut result = graph.execute(q).await.map_err(map_neo4j_error)?;
-                let row = result
-                    .next()
-                    .await
-                    .map_err(map_neo4j_error)?
+                // One statement captures the node as it was, updates it and returns
+                // both, so nothing can change it in between.
+                let mut cypher = "MATCH (n) WHERE id(n) = $id \
+                                  WITH n, properties(n) AS before, labels(n) AS before_labels"
+                    .to_string();
+                if !set_items.is_empty() {
+                    cypher.push_str(&format!(" SET {}", set_items.join(", ")));
+                }
+                if !remove_items.is_empty() {
+                    cypher.push_str(&format!(" REMOVE {}", remove_items.join(", ")));
+                }
+                cypher.push_str(" RETURN n, before, before_labels");
+
+                let mut bound = Map::new();
+                bound.insert("set".into(), Value::Object(props));
+                let q = params::bind(neo4rs::query(&cypher).param("id", id), &bound)?;
+                let mut recorded = bound.clone();
+                recorded.insert("id".into(), id.into());
+                history::statement(ctx, req, &cypher, &recorded.into())?;
+
+                let dry_run = mutation::dry_run(req);
+                let mut summary = Summary::start(&cypher);
+                let row = mutation::execute_one(&graph, q, dry_run, &mut summary)
+                    .await?
                     .ok_or(AppError::NodeNotFound { id: id_str.to_string() })?;
 
-                let node = row.get::<neo4rs::Node>("n").map_err(|e| AppError::QueryFailed {
-                    reason: e.to_string(),
-                })?;
-                let node_json = convert::node_to_json(&node);
+                // Both sides go through column_to_json so the diff compares like with like.
+                let column = |name: &str| {
+                    convert::column_to_json(&row, name).map_err(|e| AppError::QueryFailed {
+                        reason: e.to_string(),
+                    })
+                };
+                let node_json = column("n")?;
+                let mut before = column("before")?;
+                before["_id"] = node_json["_id"].clone();
+                before["_labels"] = column("before_labels")?;
+                let diff = mutation::node_diff(&before, &node_json);
 
-                Ok(CommandOutput::new(json!({
+                let outcome = json!({
                     "updated": true,
                     "node": node_json,
-                }))
+                    "diff": diff,
+                });
+                let output = CommandOutput::new(mutation::result(
+                    dry_run,
+                    outcome,
+                    summary.to_json(req)?,
+                ));
+
+                if dry_run {
+                    let command = if replace {
+                        format!("lowmain node update {id} --replace")
+                    } else {
+                        format!("lowmain node update {id}")
+                    };
+                    let mut apply = mutation::apply_action(command);
+                    for flag in ["set", "unset", "add-label", "remove-label"] {
+                        if let Some(value) = req.flag(flag) {
+                            apply = apply.with_param(format!("--{flag}"), ActionParam::new().value(value));
+                        }
+                    }
+                    return Ok(output.next_action(apply));
+                }
+
+                Ok(output
                 .next_action(NextAction::new(
                     format!("lowmain node get {id}"),
                     "View updated node",
@@ -310,9 +649,11 @@ fn update_command() -> Command {
 
 fn delete_command() -> Command {
     Command::new("delete", "Delete a node by ID")
-        .usage("lowmain node delete <id> [--detach]")
+        .usage("lowmain node delete <id> [--detach] [--dry-run]")
         .handler(|req, ctx| {
             Box::pin(async move {
+                access::ensure_writable(req, "lowmain node delete")?;
+
                 let id_str = req.arg(0).ok_or(AppError::InvalidParams {
                     reason: "Missing node ID. Usage: lowmain node delete <id>".into(),
                 })?;
@@ -329,27 +670,42 @@ fn delete_command() -> Command {
                     "MATCH (n) WHERE id(n) = $id DELETE n RETURN count(n) AS deleted"
                 };
 
-                let mut result = graph
-                    .execute(neo4rs::query(cypher).param("id", id))
-                    .await
-                    .map_err(map_neo4j_error)?;
-
-                let deleted: i64 = result
-                    .next()
-                    .await
-                    .map_err(map_neo4j_error)?
-                    .and_then(|r| r.get("deleted").ok())
-                    .unwrap_or(0);
+                history::statement(ctx, req, cypher, &json!({ "id": id }))?;
+                let dry_run = mutation::dry_run(req);
+                let mut summary = Summary::start(cypher);
+                let deleted: i64 =
+                    mutation::execute_one(&graph, neo4rs::query(cypher).param("id", id), dry_run, &mut summary)
+                        .await?
+                        .and_then(|r| r.get("deleted").ok())
+                        .unwrap_or(0);
 
                 if deleted == 0 {
                     return Err(AppError::NodeNotFound { id: id_str.to_string() }.into());
                 }
 
-                Ok(CommandOutput::new(json!({
+                let outcome = json!({
                     "deleted": true,
                     "id": id,
                     "detach": detach,
-                }))
+                });
+                let output = CommandOutput::new(mutation::result(
+                    dry_run,
+                    outcome,
+                    summary.to_json(req)?,
+                ));
+
+                if dry_run {
+                    let apply = if detach {
+                        format!("lowmain node delete {id} --detach")
+                    } else {
+                        format!("lowmain node delete {id}")
+                    };
+                    return Ok(output
+                        .next_action(mutation::apply_action(apply))
+                        .next_action(NextAction::new(format!("lowmain node get {id}"), "View the node")));
+                }
+
+                Ok(output
                 .next_action(NextAction::new("lowmain schema", "Explore database structure"))
                 .next_action(
                     NextAction::new("lowmain node find", "Find nodes")
@@ -361,10 +717,11 @@ fn delete_command() -> Command {
 
 pub fn register() -> Command {
     Command::new("node", "Node CRUD operations")
-        .usage("lowmain node [find|get|create|update|delete]")
+        .usage("lowmain node [find|get|create|merge|update|delete]")
         .subcommand(find_command())
         .subcommand(get_command())
         .subcommand(create_command())
+        .subcommand(merge_command())
         .subcommand(update_command())
         .subcommand(delete_command())
 }
@@ -1,15 +1,20 @@
 use agcli::{ActionParam, Command, CommandOutput, NextAction};
 use serde_json::json;
 
+use crate::access;
 use crate::neo4j_client;
 
 pub fn register() -> Command {
-    Command::new("ping", "Test Neo4j connection health")
-        .usage("lowmain ping [--uri=<uri>] [--user=<user>] [--password=<pw>] [--db=<db>]")
+    Command::new(
+        "ping",
+        "Test Neo4j connection health. TLS comes from a +s URI scheme plus an optional --ca-cert; client certificates and skipping verification are not supported by the Bolt driver",
+    )
+        .usage("lowmain ping [--profile=<name>] [--uri=<uri>] [--user=<user>] [--password=<pw>] [--db=<db>] [--ca-cert=<pem>] [--read-only]")
         .handler(|req, ctx| {
             Box::pin(async move {
                 let graph = neo4j_client::from_request(req, ctx).await?;
-                let (uri, db) = neo4j_client::connection_info(req);
+                let (uri, db) = neo4j_client::connection_info(req)?;
+                let profile = neo4j_client::profile_name(req)?;
 
                 let mut result = graph.execute(neo4rs::query("RETURN 1 AS ok")).await
                     .map_err(crate::error::map_neo4j_error)?;
@@ -17,20 +22,22 @@ pub fn register() -> Command {
                 let _row = result.next().await
                     .map_err(crate::error::map_neo4j_error)?;
 
+                let next_actions = vec![
+                    NextAction::new("lowmain schema", "Explore database structure"),
+                    NextAction::new("lowmain query", "Execute a Cypher query")
+                        .with_param("cypher", ActionParam::new().description("Cypher query to run").required(true)),
+                    NextAction::new("lowmain node find", "Find nodes by label")
+                        .with_param("--label", ActionParam::new().description("Node label to search").required(true)),
+                ];
+
                 Ok(CommandOutput::new(json!({
                     "connected": true,
                     "uri": uri,
                     "db": db,
+                    "profile": profile,
+                    "read_only": access::read_only(req)?,
                 }))
-                .next_action(NextAction::new("lowmain schema", "Explore database structure"))
-                .next_action(
-                    NextAction::new("lowmain query", "Execute a Cypher query")
-                        .with_param("cypher", ActionParam::new().description("Cypher query to run").required(true)),
-                )
-                .next_action(
-                    NextAction::new("lowmain node find", "Find nodes by label")
-                        .with_param("--label", ActionParam::new().description("Node label to search").required(true)),
-                ))
+                .next_actions(access::filter_actions(req, next_actions)?))
             })
         })
 }
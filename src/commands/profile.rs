@@ -0,0 +1,207 @@
+use agcli::{ActionParam, Command, CommandOutput, CommandRequest, NextAction};
+use serde_json::json;
+use std::path::PathBuf;
+
+use crate::config::{self, Profile, Scope};
+use crate::error::AppError;
+
+/// Config file targeted by add/remove: the project file with --project, else the user file.
+fn target_path(req: &CommandRequest<'_>) -> Result<(Scope, PathBuf), AppError> {
+    if req.flag("project").is_some() {
+        Ok((Scope::Project, config::project_config_path_for_write()?))
+    } else {
+        let path = config::user_config_path().ok_or(AppError::ConfigInvalid {
+            reason: "Cannot locate user config: set HOME, XDG_CONFIG_HOME or LOWMAIN_CONFIG".into(),
+        })?;
+        Ok((Scope::User, path))
+    }
+}
+
+fn list_command() -> Command {
+    Command::new("list", "List configured connection profiles")
+        .usage("lowmain profile list")
+        .handler(|_req, _ctx| {
+            Box::pin(async move {
+                let (user, project) = config::load_all()?;
+                let default = project
+                    .default_profile
+                    .clone()
+                    .or_else(|| user.default_profile.clone());
+
+                let mut profiles = Vec::new();
+                for (scope, file) in [(Scope::Project, &project), (Scope::User, &user)] {
+                    for (name, profile) in &file.profiles {
+                        let shadowed = scope == Scope::User && project.profiles.contains_key(name);
+                        profiles.push(json!({
+                            "name": name,
+                            "scope": scope.as_str(),
+                            "uri": profile.uri,
+                            "db": profile.db,
+                            "default": default.as_deref() == Some(name.as_str()),
+                            "shadowed": shadowed,
+                        }));
+                    }
+                }
+
+                let next_actions: Vec<NextAction> = user
+                    .profiles
+                    .keys()
+                    .chain(project.profiles.keys())
+                    .take(5)
+                    .map(|name| {
+                        NextAction::new(
+                            format!("lowmain ping --profile={name}"),
+                            format!("Test the {name} connection"),
+                        )
+                    })
+                    .collect();
+
+                Ok(CommandOutput::new(json!({
+                    "profiles": profiles,
+                    "default_profile": default,
+                    "user_config": config::user_config_path(),
+                    "project_config": config::project_config_path(),
+                }))
+                .next_actions(next_actions)
+                .next_action(
+                    NextAction::new("lowmain profile add", "Add a profile")
+                        .with_param("name", ActionParam::new().description("Profile name").required(true))
+                        .with_param("--uri", ActionParam::new().description("Bolt URI").required(true)),
+                ))
+            })
+        })
+}
+
+fn show_command() -> Command {
+    Command::new("show", "Show a profile without revealing secrets")
+        .usage("lowmain profile show <name>")
+        .handler(|req, _ctx| {
+            Box::pin(async move {
+                let name = req.arg(0).ok_or(AppError::InvalidParams {
+                    reason: "Missing profile name. Usage: lowmain profile show dev".into(),
+                })?;
+
+                let (user, project) = config::load_all()?;
+                let found = config::find_profile(&user, &project, name).ok_or(
+                    AppError::ProfileNotFound {
+                        name: name.to_string(),
+                    },
+                )?;
+
+                Ok(CommandOutput::new(json!({
+                    "name": found.name,
+                    "scope": found.scope.as_str(),
+                    "profile": found.profile.redacted_json(),
+                }))
+                .next_action(NextAction::new(
+                    format!("lowmain ping --profile={name}"),
+                    "Test this connection",
+                ))
+                .next_action(NextAction::new(
+                    format!("lowmain schema --profile={name}"),
+                    "Explore this database",
+                )))
+            })
+        })
+}
+
+fn add_command() -> Command {
+    Command::new("add", "Add or replace a connection profile")
+        .usage("lowmain profile add <name> [--uri=<uri>] [--user=<user>] [--db=<db>] [--password-env=<var>] [--password-file=<path>] [--ca-cert=<path>] [--default-limit=<n>] [--read-only] [--default] [--project]")
+        .handler(|req, _ctx| {
+            Box::pin(async move {
+                let name = req.arg(0).ok_or(AppError::InvalidParams {
+                    reason: "Missing profile name. Usage: lowmain profile add dev --uri=bolt://localhost:7687".into(),
+                })?;
+
+                if req.flag("password").is_some() {
+                    return Err(AppError::InvalidParams {
+                        reason: "Profiles never store passwords. Use --password-env=<VAR> or --password-file=<path>".into(),
+                    }
+                    .into());
+                }
+
+                let default_limit = match req.flag("default-limit") {
+                    Some(v) => Some(v.parse::<usize>().map_err(|_| AppError::InvalidParams {
+                        reason: format!("Invalid --default-limit: {v}"),
+                    })?),
+                    None => None,
+                };
+
+                let profile = Profile {
+                    uri: req.flag("uri").map(String::from),
+                    user: req.flag("user").map(String::from),
+                    db: req.flag("db").map(String::from),
+                    password_env: req.flag("password-env").map(String::from),
+                    password_file: req.flag("password-file").map(String::from),
+                    ca_cert: req.flag("ca-cert").map(String::from),
+                    default_limit,
+                    read_only: req.flag("read-only").map(|_| true),
+                };
+
+                let (scope, path) = target_path(req)?;
+                let mut file = config::load(&path)?;
+                let replaced = file.profiles.insert(name.to_string(), profile.clone()).is_some();
+                if req.flag("default").is_some() {
+                    file.default_profile = Some(name.to_string());
+                }
+                config::save(&path, &file)?;
+
+                Ok(CommandOutput::new(json!({
+                    "saved": true,
+                    "replaced": replaced,
+                    "name": name,
+                    "scope": scope.as_str(),
+                    "path": path,
+                    "profile": profile.redacted_json(),
+                }))
+                .next_action(NextAction::new(
+                    format!("lowmain ping --profile={name}"),
+                    "Test this connection",
+                ))
+                .next_action(NextAction::new("lowmain profile list", "List profiles")))
+            })
+        })
+}
+
+fn remove_command() -> Command {
+    Command::new("remove", "Remove a connection profile")
+        .usage("lowmain profile remove <name> [--project]")
+        .handler(|req, _ctx| {
+            Box::pin(async move {
+                let name = req.arg(0).ok_or(AppError::InvalidParams {
+                    reason: "Missing profile name. Usage: lowmain profile remove dev".into(),
+                })?;
+
+                let (scope, path) = target_path(req)?;
+                let mut file = config::load(&path)?;
+                if file.profiles.remove(name).is_none() {
+                    return Err(AppError::ProfileNotFound {
+                        name: name.to_string(),
+                    }
+                    .into());
+                }
+                if file.default_profile.as_deref() == Some(name) {
+                    file.default_profile = None;
+                }
+                config::save(&path, &file)?;
+
+                Ok(CommandOutput::new(json!({
+                    "removed": true,
+                    "name": name,
+                    "scope": scope.as_str(),
+                    "path": path,
+                }))
+                .next_action(NextAction::new("lowmain profile list", "List profiles")))
+            })
+        })
+}
+
+pub fn register() -> Command {
+    Command::new("profile", "Manage named connection profiles")
+        .usage("lowmain profile [list|show|add|remove]")
+        .subcommand(list_command())
+        .subcommand(show_command())
+        .subcommand(add_command())
+        .subcommand(remove_command())
+}
@@ -1,60 +1,463 @@
-use agcli::{ActionParam, Command, CommandOutput, NextAction};
-use serde_json::json;
+use agcli::{
+    ActionParam, Command, CommandError, CommandOutput, CommandRequest, ExecutionContext, NdjsonEmitter, NextAction,
+    StreamEvent,
+};
+use chrono::{DateTime, SecondsFormat, Utc};
+use neo4rs::{Query, Row};
+use serde_json::{Map, Value, json};
+use std::io::Stdout;
+use std::time::SystemTime;
 
+use crate::access;
+use crate::check;
 use crate::convert;
+use crate::cypher::{self, QueryType};
 use crate::error::{AppError, map_neo4j_error};
+use crate::history;
+use crate::listing;
+use crate::mutation;
 use crate::neo4j_client;
+use crate::paging;
+use crate::params;
+use crate::summary::Summary;
+
+use super::schema;
+
+/// Records pulled per round trip with --stream, so rows are written as soon as
+/// a small batch arrives whatever the page size.
+const STREAM_FETCH_SIZE: usize = 100;
+
+/// Writes rows to stdout as NDJSON `row` events as they arrive; the command
+/// envelope printed by `main` follows as the trailer line.
+struct RowEmitter(NdjsonEmitter<Stdout>);
+
+impl RowEmitter {
+    fn new() -> Self {
+        Self(NdjsonEmitter::new(std::io::stdout()))
+    }
+
+    fn emit(&mut self, row: &Row) -> Result<(), AppError> {
+        let event = StreamEvent::Event {
+            name: "row".into(),
+            data: convert::row_to_json(row),
+            ts: DateTime::<Utc>::from(SystemTime::now()).to_rfc3339_opts(SecondsFormat::Millis, true),
+        };
+        self.0.emit(event).map_err(|e| AppError::QueryFailed {
+            reason: format!("Cannot write row stream: {e}"),
+        })
+    }
+}
+
+/// Parse the <cypher> argument of a subcommand.
+fn cypher_arg<'a>(req: &'a CommandRequest<'_>, usage: &str) -> Result<&'a str, AppError> {
+    params::positionals(req).first().copied().ok_or(AppError::InvalidParams {
+        reason: format!("Missing Cypher query. Usage: {usage}"),
+    })
+}
+
+/// Build a query with --params and --param bound, noting it in the history.
+fn bind_params(req: &CommandRequest<'_>, ctx: &mut ExecutionContext, cypher: &str) -> Result<Query, AppError> {
+    let params = params::from_request(req)?;
+    history::statement(ctx, req, cypher, &params.clone().into())?;
+    params::bind(neo4rs::query(cypher), &params)
+}
+
+/// Compile a statement with EXPLAIN without running it, so errors surface here.
+async fn explain(graph: &neo4rs::Graph, q: Query) -> Result<(), AppError> {
+    let mut result = graph.execute(q).await.map_err(map_neo4j_error)?;
+    while result.next().await.map_err(map_neo4j_error)?.is_some() {}
+    Ok(())
+}
+
+/// Labels, relationship types and property keys the database knows.
+async fn fetch_known(graph: &neo4rs::Graph, summary: &mut Summary) -> Result<check::Known, CommandError> {
+    Ok(check::Known {
+        labels: schema::fetch_labels(graph, summary).await?,
+        relationship_types: schema::fetch_rel_types(graph, summary).await?,
+        property_keys: schema::fetch_property_keys(graph, summary).await?,
+    })
+}
+
+/// `--check`: EXPLAIN the statement and fail on names the schema does not know.
+/// Names the statement writes count as known for the statements after it.
+async fn ensure_known(
+    graph: &neo4rs::Graph,
+    statement: &str,
+    params: &Map<String, Value>,
+    known: &mut check::Known,
+) -> Result<(), AppError> {
+    // Schema commands cannot be explained.
+    if cypher::query_type(statement) != QueryType::Schema {
+        explain(graph, params::bind(neo4rs::query(&format!("EXPLAIN {statement}")), params)?).await?;
+    }
+    let refs = check::references(statement);
+    let unknown = check::unknown(&refs, known);
+    if !unknown.is_empty() {
+        return Err(AppError::UnknownSchemaNames {
+            names: check::describe_all(&unknown),
+        });
+    }
+    known.learn(&refs);
+    Ok(())
+}
+
+fn check_command() -> Command {
+    Command::new("check", "Validate a query with EXPLAIN and report names missing from the schema")
+        .usage("lowmain query check <cypher> [--params=<json>|@file] [--param=<name[:type]=value>...]")
+        .handler(|req, ctx| {
+            Box::pin(async move {
+                let cypher = cypher_arg(req, "lowmain query check \"MATCH (n:Person) RETURN n.name\"")?;
+                let q = bind_params(req, ctx, &format!("EXPLAIN {cypher}"))?;
+
+                let graph = neo4j_client::from_request(req, ctx).await?;
+                let mut summary = Summary::start(cypher);
+                explain(&graph, q).await?;
+                summary.mark_available();
+
+                let known = fetch_known(&graph, &mut summary).await?;
+                let refs = check::references(cypher);
+                let unknown = check::unknown(&refs, &known);
+
+                let next_action = if unknown.is_empty() {
+                    NextAction::new("lowmain query", "Run the query").with_param("cypher", ActionParam::new().value(cypher))
+                } else {
+                    NextAction::new("lowmain schema", "List labels, relationship types and indexes")
+                };
+
+                Ok(CommandOutput::new(json!({
+                    "cypher": cypher,
+                    "valid": true,
+                    "schema_ok": unknown.is_empty(),
+                    "unknown": unknown.iter().map(check::Unknown::to_json).collect::<Vec<_>>(),
+                    "references": refs.to_json(),
+                    "summary": summary.to_json(req)?,
+                }))
+                .next_action(next_action))
+            })
+        })
+}
+
+/// Read a script from `--file`; `-` reads stdin.
+fn read_script(path: &str) -> Result<String, AppError> {
+    let read = if path == "-" {
+        std::io::read_to_string(std::io::stdin())
+    } else {
+        std::fs::read_to_string(path)
+    };
+    read.map_err(|e| AppError::InvalidParams {
+        reason: format!("Cannot read --file {path}: {e}"),
+    })
+}
+
+/// Error for a failed script statement, keeping the driver error's code and fix.
+fn statement_error(err: neo4rs::Error, index: usize, line: usize, outcome: &str) -> CommandError {
+    let mut err = CommandError::from(map_neo4j_error(err));
+    err.message = format!("Statement {index} (line {line}) failed; {outcome}: {}", err.message);
+    err
+}
+
+/// Run a statement inside `txn`, keeping up to `limit` rows and counting the rest.
+async fn collect_in_txn(
+    txn: &mut neo4rs::Txn,
+    q: Query,
+    limit: usize,
+    summary: &mut Summary,
+) -> Result<(Vec<Value>, usize), neo4rs::Error> {
+    let mut result = txn.execute(q).await?;
+    summary.mark_available();
+    let mut rows = Vec::new();
+    let mut row_count = 0;
+    while let Some(row) = result.next(txn.handle()).await? {
+        if rows.len() < limit {
+            rows.push(convert::row_to_json(&row));
+        }
+        row_count += 1;
+    }
+    Ok((rows, row_count))
+}
+
+/// Like [`collect_in_txn`], in an auto-commit transaction of its own.
+async fn collect(
+    graph: &neo4rs::Graph,
+    q: Query,
+    limit: usize,
+    summary: &mut Summary,
+) -> Result<(Vec<Value>, usize), neo4rs::Error> {
+    let mut result = graph.execute(q).await?;
+    summary.mark_available();
+    let mut rows = Vec::new();
+    let mut row_count = 0;
+    while let Some(row) = result.next().await? {
+        if rows.len() < limit {
+            rows.push(convert::row_to_json(&row));
+        }
+        row_count += 1;
+    }
+    Ok((rows, row_count))
+}
+
+/// `query --file`: run every statement of a script in one transaction, or one
+/// transaction per statement with --autocommit.
+async fn run_script(
+    req: &CommandRequest<'_>,
+    ctx: &mut ExecutionContext,
+    path: &str,
+) -> Result<CommandOutput, CommandError> {
+    if !params::positionals(req).is_empty() {
+        return Err(AppError::InvalidParams {
+            reason: "Pass either a Cypher argument or --file, not both".into(),
+        }
+        .into());
+    }
+    for flag in ["stream", "cursor", "shape"] {
+        if req.flag(flag).is_some() {
+            return Err(AppError::InvalidParams {
+                reason: format!("--{flag} is not supported with --file"),
+            }
+            .into());
+        }
+    }
+
+    let script = read_script(path)?;
+    let statements = cypher::split_statements(&script);
+    if statements.is_empty() {
+        return Err(AppError::InvalidParams {
+            reason: format!("--file {path} contains no statements"),
+        }
+        .into());
+    }
+
+    let autocommit = req.flag("autocommit").is_some();
+    let dry_run = mutation::dry_run(req);
+    if autocommit && dry_run {
+        return Err(AppError::InvalidParams {
+            reason: "--dry-run needs a single transaction to roll back and cannot be combined with --autocommit".into(),
+        }
+        .into());
+    }
+    let is_write = req.flag("write").is_some() || dry_run;
+    if is_write {
+        access::ensure_writable(req, "lowmain query --write")?;
+    }
+    if !is_write {
+        for (index, statement) in statements.iter().enumerate() {
+            if let Some(reason) = cypher::write_reason(&statement.text) {
+                return Err(AppError::WriteNotPermitted {
+                    reason: format!("statement {index} (line {}): {reason}", statement.line),
+                }
+                .into());
+            }
+        }
+    }
+
+    let params = params::from_request(req)?;
+    // Bind every statement up front so a bad parameter fails before anything runs.
+    let queries = statements
+        .iter()
+        .map(|statement| params::bind(neo4rs::query(&statement.text), &params))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let limit = neo4j_client::limit(req)?;
+    let graph = neo4j_client::from_request(req, ctx).await?;
+    let mut summary = Summary::start(&script);
+
+    if req.flag("check").is_some() {
+        let mut known = fetch_known(&graph, &mut Summary::for_type(QueryType::Read)).await?;
+        for (index, statement) in statements.iter().enumerate() {
+            if let Err(e) = ensure_known(&graph, &statement.text, &params, &mut known).await {
+                let mut err = CommandError::from(e);
+                err.message = format!("Statement {index} (line {}) failed the check; nothing was run: {}", statement.line, err.message);
+                return Err(err);
+            }
+        }
+    }
+
+    // Without --autocommit every statement shares one transaction, committed at the end.
+    // Without --write there is nothing to commit, so --autocommit is ignored and the
+    // shared transaction is rolled back.
+    let autocommit = autocommit && is_write;
+    let mut txn = if autocommit {
+        None
+    } else {
+        Some(graph.start_txn().await.map_err(map_neo4j_error)?)
+    };
+    let failure = if autocommit {
+        "earlier statements were committed"
+    } else {
+        "the transaction was rolled back"
+    };
+
+    let mut results = Vec::with_capacity(statements.len());
+    for (index, (statement, q)) in statements.iter().zip(queries).enumerate() {
+        history::statement(ctx, req, &statement.text, &params.clone().into())?;
+        let mut statement_summary = Summary::start(&statement.text);
+
+        let outcome = match &mut txn {
+            Some(txn) => collect_in_txn(txn, q, limit, &mut statement_summary).await,
+            None => collect(&graph, q, limit, &mut statement_summary).await,
+        };
+        let (rows, row_count) = match outcome {
+            Ok(collected) => collected,
+            Err(e) => {
+                if let Some(txn) = txn.take() {
+                    // The failed statement already aborted the transaction server-side.
+                    let _ = txn.rollback().await;
+                }
+                return Err(statement_error(e, index, statement.line, failure));
+            }
+        };
+        summary.mark_available();
+
+        results.push(json!({
+            "index": index,
+            "line": statement.line,
+            "cypher": statement.text,
+            "rows": rows,
+            "row_count": row_count,
+            "truncated": row_count > rows.len(),
+            "summary": statement_summary.to_json(req)?,
+        }));
+    }
+
+    if let Some(txn) = txn.take() {
+        if !is_write {
+            access::discard(txn).await;
+        } else if dry_run {
+            txn.rollback().await.map_err(map_neo4j_error)?;
+        } else {
+            txn.commit().await.map_err(map_neo4j_error)?;
+        }
+    }
+
+    let mut output = json!({
+        "file": path,
+        "mode": if autocommit { "autocommit" } else { "transaction" },
+        "statements": results,
+        "summary": summary.to_json(req)?,
+    });
+    if dry_run {
+        output["dry_run"] = json!(true);
+    }
+
+    Ok(CommandOutput::new(output).next_action(NextAction::new("lowmain schema", "Check schema after the script")))
+}
 
 pub fn register() -> Command {
     Command::new("query", "Execute a raw Cypher query")
-        .usage("lowmain query <cypher> [--params=<json>] [--limit=<n>] [--write]")
+        .subcommand(check_command())
+        .usage("lowmain query [check] <cypher> [--params=<json>|@file] [--param=<name[:type]=value>...] [--limit=<n>] [--page-size=<n>] [--skip=<n>] [--cursor=<token>] [--shape=table|graph] [--with-rows] [--stream] [--write] [--dry-run] [--check] [--file=<path>|-] [--autocommit]")
         .handler(|req, ctx| {
             Box::pin(async move {
-                let cypher = req.arg(0).ok_or(AppError::InvalidParams {
-                    reason: "Missing Cypher query. Usage: lowmain query \"MATCH (n) RETURN n\"".into(),
-                })?;
+                if let Some(path) = req.flag("file") {
+                    return run_script(req, ctx, path).await;
+                }
 
-                let limit: usize = req
-                    .flag("limit")
-                    .and_then(|v| v.parse().ok())
-                    .unwrap_or(100);
+                let cypher = params::positionals(req).first().copied().ok_or(AppError::InvalidParams {
+                    reason: "Missing Cypher query. Usage: lowmain query \"MATCH (n) RETURN n\" or lowmain query --file=script.cypher".into(),
+                })?;
 
-                let is_write = req.flag("write").is_some();
+                // Merged --params/--param, re-emitted as one --params in follow-up actions.
+                let params = params::from_request(req)?;
+                let params_flag = (!params.is_empty()).then(|| Value::Object(params.clone()).to_string());
+                let page = paging::page(req, &["query", cypher, params_flag.as_deref().unwrap_or("")])?;
+                let stream = req.flag("stream").is_some();
+                // A stream does not hold rows in memory, so it only stops early when asked to.
+                let unbounded = stream && req.flag("limit").is_none() && req.flag("page-size").is_none();
+                let limit = if unbounded { usize::MAX } else { page.size };
 
-                let graph = neo4j_client::from_request(req, ctx).await?;
+                // A dry run previews a write and rolls it back, so it implies --write.
+                let dry_run = mutation::dry_run(req);
+                let is_write = req.flag("write").is_some() || dry_run;
+                if is_write {
+                    access::ensure_writable(req, "lowmain query --write")?;
+                }
+                // Reads run in a transaction that is rolled back; classifying the
+                // statement first only reports an obvious write before connecting.
+                if !is_write && let Some(reason) = cypher::write_reason(cypher) {
+                    return Err(AppError::WriteNotPermitted { reason }.into());
+                }
 
-                // Build parameterized query
-                let mut q = neo4rs::query(cypher);
-
-                if let Some(params_str) = req.flag("params") {
-                    let params: serde_json::Map<String, serde_json::Value> =
-                        serde_json::from_str(params_str).map_err(|e| AppError::InvalidParams {
-                            reason: format!("Invalid --params JSON: {e}"),
-                        })?;
-                    for (key, val) in params {
-                        q = match val {
-                            serde_json::Value::String(s) => q.param(&key, s),
-                            serde_json::Value::Number(n) => {
-                                if let Some(i) = n.as_i64() {
-                                    q.param(&key, i)
-                                } else if let Some(f) = n.as_f64() {
-                                    q.param(&key, f)
-                                } else {
-                                    q.param(&key, n.to_string())
-                                }
-                            }
-                            serde_json::Value::Bool(b) => q.param(&key, b),
-                            _ => q.param(&key, val.to_string()),
-                        };
+                let graph_shape = match req.flag("shape").unwrap_or("table") {
+                    "table" => false,
+                    "graph" => true,
+                    other => {
+                        return Err(AppError::InvalidParams {
+                            reason: format!("Invalid --shape: {other}. Use table or graph"),
+                        }
+                        .into());
+                    }
+                };
+                if stream && graph_shape {
+                    return Err(AppError::InvalidParams {
+                        reason: "--stream emits rows as they arrive and cannot be combined with --shape=graph".into(),
                     }
+                    .into());
                 }
 
-                if is_write {
+                // A result sorted by returned columns resumes after the last row's
+                // values for them; any other result resumes at the row offset.
+                let sort_columns = cypher::result_order(cypher);
+                let terms = sort_columns.as_ref().map(|columns| {
+                    columns
+                        .iter()
+                        .map(|(name, descending)| paging::SortTerm {
+                            expr: format!("`{}`", name.replace('`', "``")),
+                            descending: *descending,
+                        })
+                        .collect::<Vec<_>>()
+                });
+                let mut run_params = params.clone();
+                let statement = match (&page.after, &terms) {
+                    (Some(after), Some(terms)) if !is_write => format!(
+                        "CALL {{ {} }} WITH * WHERE {} RETURN * {}",
+                        cypher.trim().trim_end_matches(';'),
+                        after.predicate(terms, &mut run_params)?,
+                        listing::order_clause(terms)
+                    ),
+                    _ => cypher.to_string(),
+                };
+                let skip = if page.after.is_some() { page.skip } else { page.offset };
+
+                let fetch_size = if stream {
+                    STREAM_FETCH_SIZE
+                } else {
+                    skip.saturating_add(limit).saturating_add(1)
+                };
+                let graph = neo4j_client::from_request_with_fetch_size(req, ctx, fetch_size).await?;
+
+                if req.flag("check").is_some() {
+                    let mut known = fetch_known(&graph, &mut Summary::for_type(QueryType::Read)).await?;
+                    ensure_known(&graph, cypher, &params, &mut known).await?;
+                }
+
+                let q = params::bind(neo4rs::query(&statement), &run_params)?;
+                history::statement(ctx, req, &statement, &run_params.clone().into())?;
+
+                let mut summary = Summary::start(&statement);
+
+                if dry_run {
+                    let would = mutation::preview(&graph, q, page.size, &mut summary).await?;
+                    let mut apply = mutation::apply_action("lowmain query --write")
+                        .with_param("cypher", ActionParam::new().value(cypher));
+                    if let Some(params_str) = &params_flag {
+                        apply = apply.with_param("--params", ActionParam::new().value(params_str.as_str()));
+                    }
+                    Ok(CommandOutput::new(json!({
+                        "dry_run": true,
+                        "cypher": cypher,
+                        "mode": "write",
+                        "would": would,
+                        "summary": summary.to_json(req)?,
+                    }))
+                    .next_action(apply))
+                } else if is_write {
                     graph.run(q).await.map_err(map_neo4j_error)?;
+                    summary.mark_available();
                     Ok(CommandOutput::new(json!({
                         "executed": true,
                         "cypher": cypher,
                         "mode": "write",
+                        "summary": summary.to_json(req)?,
                     }))
                     .next_action(NextAction::new("lowmain schema", "Check schema after mutation"))
                     .next_action(
@@ -62,26 +465,99 @@ pub fn register() -> Command {
                             .with_param("cypher", ActionParam::new().required(true)),
                     ))
                 } else {
-                    let mut result = graph.execute(q).await.map_err(map_neo4j_error)?;
+                    let mut txn = access::read_txn(&graph).await?;
+                    let mut result = txn.execute(q).await.map_err(map_neo4j_error)?;
+                    summary.mark_available();
+                    let mut emitter = stream.then(RowEmitter::new);
                     let mut rows = Vec::new();
+                    let mut count = 0;
+
+                    let sort_key = |row: &Row| -> Option<Vec<Value>> {
+                        let columns = sort_columns.as_ref()?;
+                        columns.iter().map(|(name, _)| convert::column_to_json(row, name).ok()).collect()
+                    };
+                    let mut key_run = paging::KeyRun::new(page.after.as_ref());
+
+                    // Rows before the page are skipped client-side: --skip, the rows tied
+                    // with the cursor position, or every earlier page when the result has
+                    // no sort key to resume from. Those are only stable with ORDER BY.
+                    for _ in 0..skip {
+                        let Some(row) = result.next(txn.handle()).await.map_err(map_neo4j_error)? else {
+                            break;
+                        };
+                        key_run.push(sort_key(&row));
+                    }
 
-                    while let Some(row) = result.next().await.map_err(map_neo4j_error)? {
-                        if rows.len() >= limit {
+                    // The driver pulls at most skip + limit + 1 records per request (up to
+                    // its batch cap), so a small page never makes the server produce more
+                    // than one row past it.
+                    while count < limit {
+                        let Some(row) = result.next(txn.handle()).await.map_err(map_neo4j_error)? else {
                             break;
+                        };
+                        key_run.push(sort_key(&row));
+                        match &mut emitter {
+                            Some(emitter) => emitter.emit(&row)?,
+                            None => rows.push(convert::row_to_json(&row)),
                         }
-                        rows.push(convert::row_to_json(&row));
+                        count += 1;
                     }
+                    // One extra row tells whether the result was cut short; the rest of
+                    // the stream is discarded with the transaction.
+                    let truncated = count == limit && result.next(txn.handle()).await.map_err(map_neo4j_error)?.is_some();
+                    drop(result);
+                    access::discard(txn).await;
 
-                    let count = rows.len();
-                    let truncated = count >= limit;
+                    let next_cursor = truncated.then(|| page.next_cursor(count, key_run.position()));
+                    let mut next_actions = Vec::new();
+                    if let Some(cursor) = &next_cursor {
+                        let mut next_page = paging::Page::next_action("lowmain query", cursor)
+                            .with_param("cypher", ActionParam::new().value(cypher));
+                        if let Some(params_str) = &params_flag {
+                            next_page = next_page.with_param("--params", ActionParam::new().value(params_str.as_str()));
+                        }
+                        next_actions.push(next_page);
+                    }
+                    let mut page_json = page.to_json(truncated, next_cursor.as_deref());
+                    page_json["ordered"] = json!(cypher::orders_result(cypher));
 
-                    Ok(CommandOutput::new(json!({
+                    if stream {
+                        return Ok(CommandOutput::new(json!({
+                            "cypher": cypher,
+                            "streamed": true,
+                            "count": count,
+                            "truncated": truncated,
+                            "limit": (!unbounded).then_some(limit),
+                            "page": page_json,
+                            "summary": summary.to_json(req)?,
+                        }))
+                        .next_actions(next_actions)
+                        .next_action(
+                            NextAction::new("lowmain query", "Run another query")
+                                .with_param("cypher", ActionParam::new().required(true)),
+                        ));
+                    }
+
+                    let mut output = json!({
                         "cypher": cypher,
-                        "rows": rows,
                         "count": count,
                         "truncated": truncated,
                         "limit": limit,
-                    }))
+                        "page": page_json,
+                        "summary": summary.to_json(req)?,
+                    });
+                    if graph_shape {
+                        output["shape"] = json!("graph");
+                        output["graph"] = convert::rows_to_graph(&rows);
+                        if req.flag("with-rows").is_some() {
+                            output["rows"] = json!(rows);
+                        }
+                    } else {
+                        output["rows"] = json!(rows);
+                    }
+
+                    Ok(CommandOutput::new(output)
+                    .next_actions(next_actions)
                     .next_action(
                         NextAction::new("lowmain query", "Run another query")
                             .with_param("cypher", ActionParam::new().required(true)),
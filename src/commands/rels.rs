@@ -1,24 +1,41 @@
 use agcli::{ActionParam, Command, CommandOutput, NextAction};
 use serde_json::json;
 
+use crate::access;
 use crate::convert;
 use crate::error::{AppError, map_neo4j_error};
+use crate::history;
+use crate::listing;
+use crate::mutation;
 use crate::neo4j_client;
+use crate::paging;
+use crate::params;
+use crate::summary::Summary;
 
 fn find_command() -> Command {
     Command::new("find", "Find relationships by type and/or endpoints")
-        .usage("lowmain rel find [--from=<id>] [--to=<id>] [--type=<type>] [--limit=<n>]")
+        .usage("lowmain rel find [--from=<id>] [--to=<id>] [--type=<type>] [--order-by=<prop[:desc]>,...] [--props=<a,b>] [--exclude-props=<a,b>] [--limit=<n>] [--page-size=<n>] [--skip=<n>] [--cursor=<token>]")
         .handler(|req, ctx| {
             Box::pin(async move {
-                let limit: usize = req
-                    .flag("limit")
-                    .and_then(|v| v.parse().ok())
-                    .unwrap_or(100);
-
                 let from_id = req.flag("from").and_then(|v| v.parse::<i64>().ok());
                 let to_id = req.flag("to").and_then(|v| v.parse::<i64>().ok());
                 let rel_type = req.flag("type");
 
+                let page = paging::page(
+                    req,
+                    &[
+                        "rel find",
+                        req.flag("from").unwrap_or(""),
+                        req.flag("to").unwrap_or(""),
+                        rel_type.unwrap_or(""),
+                        req.flag("order-by").unwrap_or(""),
+                    ],
+                )?;
+                let order = listing::order_by(req)?;
+                let projection = listing::projection(req);
+                // Pages resume after the sort key of the last row seen.
+                let terms = listing::sort_terms("r", &order);
+
                 let graph = neo4j_client::from_request(req, ctx).await?;
 
                 // Build Cypher dynamically
@@ -27,11 +44,17 @@ fn find_command() -> Command {
                     .unwrap_or_else(|| "[r]".to_string());
 
                 let mut where_clauses = Vec::new();
-                if from_id.is_some() {
+                let mut q_params = serde_json::Map::new();
+                if let Some(fid) = from_id {
                     where_clauses.push("id(a) = $from_id".to_string());
+                    q_params.insert("from_id".into(), fid.into());
                 }
-                if to_id.is_some() {
+                if let Some(tid) = to_id {
                     where_clauses.push("id(b) = $to_id".to_string());
+                    q_params.insert("to_id".into(), tid.into());
+                }
+                if let Some(after) = &page.after {
+                    where_clauses.push(format!("({})", after.predicate(&terms, &mut q_params)?));
                 }
 
                 let where_str = if where_clauses.is_empty() {
@@ -40,49 +63,89 @@ fn find_command() -> Command {
                     format!(" WHERE {}", where_clauses.join(" AND "))
                 };
 
+                let returned = match &projection {
+                    Some(projection) => {
+                        q_params.insert("projected".into(), projection.params());
+                        projection.return_item("r", listing::Entity::Relationship)
+                    }
+                    None => "r".to_string(),
+                };
+
+                // One extra row tells whether another page exists.
                 let cypher = format!(
-                    "MATCH (a)-{rel_pattern}->(b){where_str} RETURN r, id(a) AS from_id, id(b) AS to_id LIMIT {limit}"
+                    "MATCH (a)-{rel_pattern}->(b){where_str} RETURN {returned}, id(a) AS from_id, id(b) AS to_id, {} {} SKIP {} LIMIT {}",
+                    listing::sort_key_item(&terms),
+                    listing::order_clause(&terms),
+                    page.skip,
+                    page.size + 1
                 );
 
-                let mut q = neo4rs::query(&cypher);
-                if let Some(fid) = from_id {
-                    q = q.param("from_id", fid);
-                }
-                if let Some(tid) = to_id {
-                    q = q.param("to_id", tid);
-                }
+                history::statement(ctx, req, &cypher, &q_params.clone().into())?;
+                let q = params::bind(neo4rs::query(&cypher), &q_params)?;
 
+                let mut summary = Summary::start(&cypher);
                 let mut result = graph.execute(q).await.map_err(map_neo4j_error)?;
+                summary.mark_available();
                 let mut rels = Vec::new();
+                let mut has_more = false;
+                let mut last_key = None;
 
                 while let Some(row) = result.next().await.map_err(map_neo4j_error)? {
-                    if let Ok(rel) = row.get::<neo4rs::Relation>("r") {
-                        rels.push(convert::relation_to_json(&rel));
+                    if rels.len() == page.size {
+                        has_more = true;
+                        break;
+                    }
+                    let rel = match projection {
+                        Some(_) => convert::column_to_json(&row, listing::PROJECTED_COLUMN).map(listing::Projection::flatten),
+                        None => convert::column_to_json(&row, "r"),
+                    }
+                    .ok();
+                    if let Some(rel) = rel {
+                        last_key = convert::column_to_json(&row, listing::SORT_KEY_COLUMN).ok();
+                        rels.push(rel);
                     }
                 }
 
                 let count = rels.len();
+                let next_cursor = has_more.then(|| page.next_cursor(count, last_key.and_then(paging::Position::unique)));
 
-                Ok(CommandOutput::new(json!({
-                    "relationships": rels,
-                    "count": count,
-                }))
-                .next_action(
+                let mut next_actions = Vec::new();
+                if let Some(cursor) = &next_cursor {
+                    let mut next_page = paging::Page::next_action("lowmain rel find", cursor);
+                    for flag in ["from", "to", "type", "order-by", "props", "exclude-props"] {
+                        if let Some(value) = req.flag(flag) {
+                            next_page = next_page.with_param(format!("--{flag}"), ActionParam::new().value(value));
+                        }
+                    }
+                    next_actions.push(next_page);
+                }
+
+                next_actions.push(
                     NextAction::new("lowmain rel create", "Create a relationship")
                         .with_param("--from", ActionParam::new().description("Source node ID").required(true))
                         .with_param("--to", ActionParam::new().description("Target node ID").required(true))
                         .with_param("--type", ActionParam::new().description("Relationship type").required(true)),
-                )
-                .next_action(NextAction::new("lowmain schema types", "View relationship types")))
+                );
+                next_actions.push(NextAction::new("lowmain schema types", "View relationship types"));
+
+                Ok(CommandOutput::new(json!({
+                    "relationships": rels,
+                    "count": count,
+                    "page": page.to_json(has_more, next_cursor.as_deref()),
+                    "summary": summary.to_json(req)?,
+                }))
+                .next_actions(access::filter_actions(req, next_actions)?))
             })
         })
 }
 
 fn create_command() -> Command {
     Command::new("create", "Create a relationship between two nodes")
-        .usage("lowmain rel create --from=<id> --to=<id> --type=<type> [--props=<json>]")
+        .usage("lowmain rel create --from=<id> --to=<id> --type=<type> [--props=<json>] [--dry-run]")
         .handler(|req, ctx| {
             Box::pin(async move {
+                access::ensure_writable(req, "lowmain rel create")?;
+
                 let from_str = req.flag("from").ok_or(AppError::InvalidParams {
                     reason: "Missing --from. Usage: lowmain rel create --from=1 --to=2 --type=KNOWS".into(),
                 })?;
@@ -102,11 +165,10 @@ fn create_command() -> Command {
 
                 let graph = neo4j_client::from_request(req, ctx).await?;
 
-                let (_cypher, q) = if let Some(props_str) = req.flag("props") {
-                    let props: serde_json::Map<String, serde_json::Value> =
-                        serde_json::from_str(props_str).map_err(|e| AppError::InvalidParams {
-                            reason: format!("Invalid --props JSON: {e}"),
-                        })?;
+                let mut recorded = serde_json::Map::new();
+                let (cypher, q) = if let Some(props_str) = req.flag("props") {
+                    let props = params::parse_object(props_str, "props")?;
+                    recorded = props.clone();
 
                     let set_clause: String = props
                         .keys()
@@ -117,26 +179,10 @@ fn create_command() -> Command {
                     let cypher = format!(
                         "MATCH (a), (b) WHERE id(a) = $from_id AND id(b) = $to_id CREATE (a)-[r:`{rel_type}`]->(b) SET {set_clause} RETURN r"
                     );
-                    let mut q = neo4rs::query(&cypher)
+                    let q = neo4rs::query(&cypher)
                         .param("from_id", from_id)
                         .param("to_id", to_id);
-
-                    for (key, val) in &props {
-                        q = match val {
-                            serde_json::Value::String(s) => q.param(key.as_str(), s.clone()),
-                            serde_json::Value::Number(n) => {
-                                if let Some(i) = n.as_i64() {
-                                    q.param(key.as_str(), i)
-                                } else if let Some(f) = n.as_f64() {
-                                    q.param(key.as_str(), f)
-                                } else {
-                                    q.param(key.as_str(), n.to_string())
-                                }
-                            }
-                            serde_json::Value::Bool(b) => q.param(key.as_str(), *b),
-                            _ => q.param(key.as_str(), val.to_string()),
-                        };
-                    }
+                    let q = params::bind(q, &props)?;
 
                     (cypher, q)
                 } else {
@@ -149,25 +195,45 @@ fn create_command() -> Command {
                     (cypher, q)
                 };
 
-                let mut result = graph.execute(q).await.map_err(map_neo4j_error)?;
-                let row = result
-                    .next()
-                    .await
-                    .map_err(map_neo4j_error)?
+                recorded.insert("from_id".into(), from_id.into());
+                recorded.insert("to_id".into(), to_id.into());
+                history::statement(ctx, req, &cypher, &recorded.into())?;
+
+                let dry_run = mutation::dry_run(req);
+                let mut summary = Summary::start(&cypher);
+                let row = mutation::execute_one(&graph, q, dry_run, &mut summary)
+                    .await?
                     .ok_or(AppError::QueryFailed {
                         reason: "CREATE did not return a relationship — check that both nodes exist".into(),
                     })?;
 
-                let rel = row.get::<neo4rs::Relation>("r").map_err(|e| AppError::QueryFailed {
+                let rel_json = convert::column_to_json(&row, "r").map_err(|e| AppError::QueryFailed {
                     reason: e.to_string(),
                 })?;
-                let rel_json = convert::relation_to_json(&rel);
-                let rel_id = rel.id();
+                let rel_id = rel_json["_id"].as_i64().unwrap_or_default();
 
-                Ok(CommandOutput::new(json!({
+                let outcome = json!({
                     "created": true,
                     "relationship": rel_json,
-                }))
+                });
+                let output = CommandOutput::new(mutation::result(
+                    dry_run,
+                    outcome,
+                    summary.to_json(req)?,
+                ));
+
+                if dry_run {
+                    let mut apply = mutation::apply_action("lowmain rel create")
+                        .with_param("--from", ActionParam::new().value(from_str))
+                        .with_param("--to", ActionParam::new().value(to_str))
+                        .with_param("--type", ActionParam::new().value(rel_type));
+                    if let Some(props_str) = req.flag("props") {
+                        apply = apply.with_param("--props", ActionParam::new().value(props_str));
+                    }
+                    return Ok(output.next_action(apply));
+                }
+
+                Ok(output
                 .next_action(NextAction::new(
                     format!("lowmain node get {from_id}"),
                     "View source node",
@@ -186,9 +252,11 @@ fn create_command() -> Command {
 
 fn delete_command() -> Command {
     Command::new("delete", "Delete a relationship by ID")
-        .usage("lowmain rel delete <id>")
+        .usage("lowmain rel delete <id> [--dry-run]")
         .handler(|req, ctx| {
             Box::pin(async move {
+                access::ensure_writable(req, "lowmain rel delete")?;
+
                 let id_str = req.arg(0).ok_or(AppError::InvalidParams {
                     reason: "Missing relationship ID. Usage: lowmain rel delete <id>".into(),
                 })?;
@@ -198,31 +266,35 @@ fn delete_command() -> Command {
 
                 let graph = neo4j_client::from_request(req, ctx).await?;
 
-                let mut result = graph
-                    .execute(
-                        neo4rs::query(
-                            "MATCH ()-[r]->() WHERE id(r) = $id DELETE r RETURN count(r) AS deleted",
-                        )
-                        .param("id", id),
-                    )
-                    .await
-                    .map_err(map_neo4j_error)?;
-
-                let deleted: i64 = result
-                    .next()
-                    .await
-                    .map_err(map_neo4j_error)?
-                    .and_then(|r| r.get("deleted").ok())
-                    .unwrap_or(0);
+                let cypher = "MATCH ()-[r]->() WHERE id(r) = $id DELETE r RETURN count(r) AS deleted";
+                history::statement(ctx, req, cypher, &json!({ "id": id }))?;
+                let dry_run = mutation::dry_run(req);
+                let mut summary = Summary::start(cypher);
+                let deleted: i64 =
+                    mutation::execute_one(&graph, neo4rs::query(cypher).param("id", id), dry_run, &mut summary)
+                        .await?
+                        .and_then(|r| r.get("deleted").ok())
+                        .unwrap_or(0);
 
                 if deleted == 0 {
                     return Err(AppError::RelNotFound { id: id_str.to_string() }.into());
                 }
 
-                Ok(CommandOutput::new(json!({
+                let outcome = json!({
                     "deleted": true,
                     "id": id,
-                }))
+                });
+                let output = CommandOutput::new(mutation::result(
+                    dry_run,
+                    outcome,
+                    summary.to_json(req)?,
+                ));
+
+                if dry_run {
+                    return Ok(output.next_action(mutation::apply_action(format!("lowmain rel delete {id}"))));
+                }
+
+                Ok(output
                 .next_action(NextAction::new("lowmain rel find", "Find relationships"))
                 .next_action(NextAction::new("lowmain schema types", "View relationship types")))
             })
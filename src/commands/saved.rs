@@ -0,0 +1,267 @@
+use agcli::{ActionParam, Command, CommandOutput, NextAction};
+use serde_json::{Value, json};
+
+use crate::access;
+use crate::config;
+use crate::convert;
+use crate::cypher;
+use crate::error::{AppError, map_neo4j_error};
+use crate::history;
+use crate::library::{self, ParamSpec, SavedQuery};
+use crate::mutation;
+use crate::neo4j_client;
+use crate::params;
+use crate::summary::Summary;
+
+/// Parse --params-spec: a JSON array of parameter declarations, or `@file`.
+fn parse_specs(raw: &str) -> Result<Vec<ParamSpec>, AppError> {
+    let contents;
+    let json = match raw.strip_prefix('@') {
+        Some(path) => {
+            contents = std::fs::read_to_string(path).map_err(|e| AppError::InvalidParams {
+                reason: format!("Cannot read --params-spec file {path}: {e}"),
+            })?;
+            contents.as_str()
+        }
+        None => raw,
+    };
+    serde_json::from_str(json).map_err(|e| AppError::InvalidParams {
+        reason: format!(
+            "Invalid --params-spec: {e}. Expected [{{\"name\":\"limit\",\"type\":\"int\",\"required\":false,\"default\":10,\"description\":\"...\"}}]"
+        ),
+    })
+}
+
+fn spec_json(saved: &SavedQuery) -> Value {
+    json!({
+        "cypher": saved.cypher,
+        "description": saved.description,
+        "params": saved.params,
+        "query_type": cypher::query_type(&saved.cypher).as_str(),
+    })
+}
+
+fn list_command() -> Command {
+    Command::new("list", "List saved queries")
+        .usage("lowmain saved list")
+        .handler(|_req, _ctx| {
+            Box::pin(async move {
+                let (path, library) = library::load()?;
+
+                let queries: Vec<Value> = library
+                    .queries
+                    .iter()
+                    .map(|(name, saved)| {
+                        json!({
+                            "name": name,
+                            "description": saved.description,
+                            "params": saved.params.iter().map(|p| p.name.as_str()).collect::<Vec<_>>(),
+                        })
+                    })
+                    .collect();
+
+                let next_actions: Vec<NextAction> = library
+                    .queries
+                    .iter()
+                    .take(10)
+                    .map(|(name, saved)| saved.run_action(name))
+                    .collect();
+
+                Ok(CommandOutput::new(json!({
+                    "queries": queries,
+                    "count": queries.len(),
+                    "path": path,
+                }))
+                .next_actions(next_actions)
+                .next_action(
+                    NextAction::new("lowmain saved add", "Save a query")
+                        .with_param("name", ActionParam::new().description("Query name").required(true))
+                        .with_param("cypher", ActionParam::new().description("Cypher with $params").required(true)),
+                ))
+            })
+        })
+}
+
+fn show_command() -> Command {
+    Command::new("show", "Show a saved query and its parameters")
+        .usage("lowmain saved show <name>")
+        .handler(|req, _ctx| {
+            Box::pin(async move {
+                let name = req.arg(0).ok_or(AppError::InvalidParams {
+                    reason: "Missing query name. Usage: lowmain saved show top-people".into(),
+                })?;
+
+                let (_, library) = library::load()?;
+                let saved = library.get(name)?;
+
+                Ok(CommandOutput::new(json!({
+                    "name": name,
+                    "query": spec_json(saved),
+                }))
+                .next_action(saved.run_action(name))
+                .next_action(
+                    NextAction::new("lowmain query check", "Check the saved query against the schema")
+                        .with_param("cypher", ActionParam::new().value(saved.cypher.as_str())),
+                ))
+            })
+        })
+}
+
+fn add_command() -> Command {
+    Command::new("add", "Save a named, parameterized query")
+        .usage("lowmain saved add <name> <cypher> [--description=<text>] [--params-spec=<json>|@file]")
+        .handler(|req, _ctx| {
+            Box::pin(async move {
+                let usage = "Usage: lowmain saved add top-people \"MATCH (p:Person) RETURN p LIMIT $limit\"";
+                let name = req.arg(0).ok_or(AppError::InvalidParams {
+                    reason: format!("Missing query name. {usage}"),
+                })?;
+                let cypher = req.arg(1).ok_or(AppError::InvalidParams {
+                    reason: format!("Missing Cypher. {usage}"),
+                })?;
+
+                let specs = match req.flag("params-spec") {
+                    Some(raw) => parse_specs(raw)?,
+                    None => Vec::new(),
+                };
+                let saved = SavedQuery::new(cypher, req.flag("description").map(String::from), specs)?;
+
+                let (path, mut library) = library::load()?;
+                let replaced = library.queries.insert(name.to_string(), saved.clone()).is_some();
+                config::save_json(&path, &library)?;
+
+                Ok(CommandOutput::new(json!({
+                    "saved": true,
+                    "replaced": replaced,
+                    "name": name,
+                    "path": path,
+                    "query": spec_json(&saved),
+                }))
+                .next_action(saved.run_action(name))
+                .next_action(NextAction::new("lowmain saved list", "List saved queries")))
+            })
+        })
+}
+
+fn remove_command() -> Command {
+    Command::new("remove", "Remove a saved query")
+        .usage("lowmain saved remove <name>")
+        .handler(|req, _ctx| {
+            Box::pin(async move {
+                let name = req.arg(0).ok_or(AppError::InvalidParams {
+                    reason: "Missing query name. Usage: lowmain saved remove top-people".into(),
+                })?;
+
+                let (path, mut library) = library::load()?;
+                if library.queries.remove(name).is_none() {
+                    return Err(AppError::SavedQueryNotFound {
+                        name: name.to_string(),
+                    }
+                    .into());
+                }
+                config::save_json(&path, &library)?;
+
+                Ok(CommandOutput::new(json!({
+                    "removed": true,
+                    "name": name,
+                    "path": path,
+                }))
+                .next_action(NextAction::new("lowmain saved list", "List saved queries")))
+            })
+        })
+}
+
+fn run_command() -> Command {
+    Command::new("run", "Run a saved query after validating its parameters")
+        .usage("lowmain saved run <name> [--param=<name[:type]=value>...] [--params=<json>|@file] [--limit=<n>] [--write] [--dry-run]")
+        .handler(|req, ctx| {
+            Box::pin(async move {
+                let positionals = params::positionals(req);
+                let name = *positionals.first().ok_or(AppError::InvalidParams {
+                    reason: "Missing query name. Usage: lowmain saved run top-people --param limit=5".into(),
+                })?;
+
+                let (_, library) = library::load()?;
+                let saved = library.get(name)?;
+
+                // Untyped --param values take the declared type, so `--param zip=02139`
+                // stays a string when the query declares it as one.
+                let supplied = params::from_request_typed(req, &|p| saved.declared_type(p))?;
+                let resolved = saved.resolve(supplied)?;
+
+                let dry_run = mutation::dry_run(req);
+                let is_write = req.flag("write").is_some() || dry_run;
+                if is_write {
+                    access::ensure_writable(req, "lowmain saved run --write")?;
+                } else if let Some(reason) = cypher::write_reason(&saved.cypher) {
+                    return Err(AppError::WriteNotPermitted { reason }.into());
+                }
+
+                let limit = neo4j_client::limit(req)?;
+                let graph = neo4j_client::from_request_with_fetch_size(req, ctx, limit.saturating_add(1)).await?;
+                let q = params::bind(neo4rs::query(&saved.cypher), &resolved)?;
+                history::statement(ctx, req, &saved.cypher, &resolved.clone().into())?;
+                let mut summary = Summary::start(&saved.cypher);
+
+                if dry_run {
+                    let would = mutation::preview(&graph, q, limit, &mut summary).await?;
+                    return Ok(CommandOutput::new(json!({
+                        "name": name,
+                        "params": resolved,
+                        "dry_run": true,
+                        "would": would,
+                        "summary": summary.to_json(req)?,
+                    })));
+                }
+
+                if is_write {
+                    graph.run(q).await.map_err(map_neo4j_error)?;
+                    summary.mark_available();
+                    return Ok(CommandOutput::new(json!({
+                        "name": name,
+                        "params": resolved,
+                        "executed": true,
+                        "mode": "write",
+                        "summary": summary.to_json(req)?,
+                    }))
+                    .next_action(NextAction::new("lowmain schema", "Check schema after mutation")));
+                }
+
+                // Without --write the statement runs in a transaction that is rolled back.
+                let mut txn = access::read_txn(&graph).await?;
+                let mut result = txn.execute(q).await.map_err(map_neo4j_error)?;
+                summary.mark_available();
+                let mut rows = Vec::new();
+                while rows.len() < limit {
+                    let Some(row) = result.next(txn.handle()).await.map_err(map_neo4j_error)? else {
+                        break;
+                    };
+                    rows.push(convert::row_to_json(&row));
+                }
+                let truncated = rows.len() == limit && result.next(txn.handle()).await.map_err(map_neo4j_error)?.is_some();
+                drop(result);
+                access::discard(txn).await;
+
+                Ok(CommandOutput::new(json!({
+                    "name": name,
+                    "params": resolved,
+                    "rows": rows,
+                    "count": rows.len(),
+                    "truncated": truncated,
+                    "summary": summary.to_json(req)?,
+                }))
+                .next_action(saved.run_action(name))
+                .next_action(NextAction::new(format!("lowmain saved show {name}"), "Show the saved query")))
+            })
+        })
+}
+
+pub fn register() -> Command {
+    Command::new("saved", "Manage and run saved parameterized queries")
+        .usage("lowmain saved [list|show|add|remove|run]")
+        .subcommand(list_command())
+        .subcommand(show_command())
+        .subcommand(add_command())
+        .subcommand(remove_command())
+        .subcommand(run_command())
+}
@@ -1,8 +1,11 @@
 use agcli::{ActionParam, Command, CommandOutput, NextAction};
 use serde_json::json;
 
+use crate::access;
 use crate::error::map_neo4j_error;
 use crate::neo4j_client;
+use crate::cypher::QueryType;
+use crate::summary::Summary;
 
 fn labels_command() -> Command {
     Command::new("labels", "List all node labels")
@@ -10,7 +13,8 @@ fn labels_command() -> Command {
         .handler(|req, ctx| {
             Box::pin(async move {
                 let graph = neo4j_client::from_request(req, ctx).await?;
-                let labels = fetch_labels(&graph).await?;
+                let mut summary = Summary::for_type(QueryType::Read);
+                let labels = fetch_labels(&graph, &mut summary).await?;
 
                 let next_actions = labels
                     .iter()
@@ -22,7 +26,10 @@ fn labels_command() -> Command {
                     })
                     .collect::<Vec<_>>();
 
-                Ok(CommandOutput::new(json!({ "labels": labels })).next_actions(next_actions))
+                Ok(CommandOutput::new(json!({
+                    "labels": labels,
+                    "summary": summary.to_json(req)?,
+                })).next_actions(next_actions))
             })
         })
 }
@@ -33,7 +40,8 @@ fn types_command() -> Command {
         .handler(|req, ctx| {
             Box::pin(async move {
                 let graph = neo4j_client::from_request(req, ctx).await?;
-                let types = fetch_rel_types(&graph).await?;
+                let mut summary = Summary::for_type(QueryType::Read);
+                let types = fetch_rel_types(&graph, &mut summary).await?;
 
                 let next_actions = types
                     .iter()
@@ -45,7 +53,10 @@ fn types_command() -> Command {
                     })
                     .collect::<Vec<_>>();
 
-                Ok(CommandOutput::new(json!({ "relationship_types": types }))
+                Ok(CommandOutput::new(json!({
+                    "relationship_types": types,
+                    "summary": summary.to_json(req)?,
+                }))
                     .next_actions(next_actions))
             })
         })
@@ -57,8 +68,12 @@ fn indexes_command() -> Command {
         .handler(|req, ctx| {
             Box::pin(async move {
                 let graph = neo4j_client::from_request(req, ctx).await?;
-                let indexes = fetch_indexes(&graph).await?;
-                Ok(CommandOutput::new(json!({ "indexes": indexes }))
+                let mut summary = Summary::for_type(QueryType::Read);
+                let indexes = fetch_indexes(&graph, &mut summary).await?;
+                Ok(CommandOutput::new(json!({
+                    "indexes": indexes,
+                    "summary": summary.to_json(req)?,
+                }))
                     .next_action(NextAction::new("lowmain schema constraints", "View constraints")))
             })
         })
@@ -70,8 +85,12 @@ fn constraints_command() -> Command {
         .handler(|req, ctx| {
             Box::pin(async move {
                 let graph = neo4j_client::from_request(req, ctx).await?;
-                let constraints = fetch_constraints(&graph).await?;
-                Ok(CommandOutput::new(json!({ "constraints": constraints }))
+                let mut summary = Summary::for_type(QueryType::Read);
+                let constraints = fetch_constraints(&graph, &mut summary).await?;
+                Ok(CommandOutput::new(json!({
+                    "constraints": constraints,
+                    "summary": summary.to_json(req)?,
+                }))
                     .next_action(NextAction::new("lowmain schema indexes", "View indexes")))
             })
         })
@@ -83,6 +102,7 @@ fn count_command() -> Command {
         .handler(|req, ctx| {
             Box::pin(async move {
                 let graph = neo4j_client::from_request(req, ctx).await?;
+                let mut summary = Summary::for_type(QueryType::Read);
 
                 let mut result = graph
                     .execute(neo4rs::query(
@@ -90,6 +110,7 @@ fn count_command() -> Command {
                     ))
                     .await
                     .map_err(map_neo4j_error)?;
+                summary.mark_available();
                 let node_count: i64 = result
                     .next()
                     .await
@@ -113,6 +134,7 @@ fn count_command() -> Command {
                 Ok(CommandOutput::new(json!({
                     "node_count": node_count,
                     "relationship_count": rel_count,
+                    "summary": summary.to_json(req)?,
                 }))
                 .next_action(NextAction::new("lowmain schema labels", "View labels"))
                 .next_action(NextAction::new("lowmain schema types", "View relationship types")))
@@ -131,11 +153,12 @@ pub fn register() -> Command {
         .handler(|req, ctx| {
             Box::pin(async move {
                 let graph = neo4j_client::from_request(req, ctx).await?;
+                let mut summary = Summary::for_type(QueryType::Read);
 
-                let labels = fetch_labels(&graph).await?;
-                let types = fetch_rel_types(&graph).await?;
-                let indexes = fetch_indexes(&graph).await?;
-                let constraints = fetch_constraints(&graph).await?;
+                let labels = fetch_labels(&graph, &mut summary).await?;
+                let types = fetch_rel_types(&graph, &mut summary).await?;
+                let indexes = fetch_indexes(&graph, &mut summary).await?;
+                let constraints = fetch_constraints(&graph, &mut summary).await?;
 
                 let mut next_actions: Vec<NextAction> = labels
                     .iter()
@@ -174,17 +197,19 @@ pub fn register() -> Command {
                     "relationship_types": types,
                     "indexes": indexes,
                     "constraints": constraints,
+                    "summary": summary.to_json(req)?,
                 }))
-                .next_actions(next_actions))
+                .next_actions(access::filter_actions(req, next_actions)?))
             })
         })
 }
 
-async fn fetch_labels(graph: &neo4rs::Graph) -> Result<Vec<String>, agcli::CommandError> {
+pub async fn fetch_labels(graph: &neo4rs::Graph, summary: &mut Summary) -> Result<Vec<String>, agcli::CommandError> {
     let mut result = graph
         .execute(neo4rs::query("CALL db.labels() YIELD label RETURN label ORDER BY label"))
         .await
         .map_err(map_neo4j_error)?;
+    summary.mark_available();
 
     let mut labels = Vec::new();
     while let Some(row) = result.next().await.map_err(map_neo4j_error)? {
@@ -195,13 +220,14 @@ async fn fetch_labels(graph: &neo4rs::Graph) -> Result<Vec<String>, agcli::Comma
     Ok(labels)
 }
 
-async fn fetch_rel_types(graph: &neo4rs::Graph) -> Result<Vec<String>, agcli::CommandError> {
+pub async fn fetch_rel_types(graph: &neo4rs::Graph, summary: &mut Summary) -> Result<Vec<String>, agcli::CommandError> {
     let mut result = graph
         .execute(neo4rs::query(
             "CALL db.relationshipTypes() YIELD relationshipType RETURN relationshipType ORDER BY relationshipType",
         ))
         .await
         .map_err(map_neo4j_error)?;
+    summary.mark_available();
 
     let mut types = Vec::new();
     while let Some(row) = result.next().await.map_err(map_neo4j_error)? {
@@ -212,11 +238,28 @@ async fn fetch_rel_types(graph: &neo4rs::Graph) -> Result<Vec<String>, agcli::Co
     Ok(types)
 }
 
-async fn fetch_indexes(graph: &neo4rs::Graph) -> Result<Vec<serde_json::Value>, agcli::CommandError> {
+pub async fn fetch_property_keys(graph: &neo4rs::Graph, summary: &mut Summary) -> Result<Vec<String>, agcli::CommandError> {
+    let mut result = graph
+        .execute(neo4rs::query("CALL db.propertyKeys() YIELD propertyKey RETURN propertyKey ORDER BY propertyKey"))
+        .await
+        .map_err(map_neo4j_error)?;
+    summary.mark_available();
+
+    let mut keys = Vec::new();
+    while let Some(row) = result.next().await.map_err(map_neo4j_error)? {
+        if let Ok(key) = row.get::<String>("propertyKey") {
+            keys.push(key);
+        }
+    }
+    Ok(keys)
+}
+
+pub async fn fetch_indexes(graph: &neo4rs::Graph, summary: &mut Summary) -> Result<Vec<serde_json::Value>, agcli::CommandError> {
     let mut result = graph
         .execute(neo4rs::query("SHOW INDEXES YIELD name, type, labelsOrTypes, properties, state"))
         .await
         .map_err(map_neo4j_error)?;
+    summary.mark_available();
 
     let mut indexes = Vec::new();
     while let Some(row) = result.next().await.map_err(map_neo4j_error)? {
@@ -225,11 +268,12 @@ async fn fetch_indexes(graph: &neo4rs::Graph) -> Result<Vec<serde_json::Value>,
     Ok(indexes)
 }
 
-async fn fetch_constraints(graph: &neo4rs::Graph) -> Result<Vec<serde_json::Value>, agcli::CommandError> {
+pub async fn fetch_constraints(graph: &neo4rs::Graph, summary: &mut Summary) -> Result<Vec<serde_json::Value>, agcli::CommandError> {
     let mut result = graph
         .execute(neo4rs::query("SHOW CONSTRAINTS YIELD name, type, labelsOrTypes, properties"))
         .await
         .map_err(map_neo4j_error)?;
+    summary.mark_available();
 
     let mut constraints = Vec::new();
     while let Some(row) = result.next().await.map_err(map_neo4j_error)? {
@@ -237,3 +281,51 @@ async fn fetch_constraints(graph: &neo4rs::Graph) -> Result<Vec<serde_json::Valu
     }
     Ok(constraints)
 }
+
+/// Whether a uniqueness or node key constraint on `label` covers a subset of
+/// `keys`, so a MERGE on those keys cannot create duplicates.
+pub fn has_unique_constraint(constraints: &[serde_json::Value], label: &str, keys: &[&str]) -> bool {
+    constraints.iter().any(|c| {
+        let kind = c["type"].as_str().unwrap_or_default();
+        let names = |field: &str| -> Vec<&str> {
+            c[field].as_array().map(|a| a.iter().filter_map(|v| v.as_str()).collect()).unwrap_or_default()
+        };
+        let props = names("properties");
+        (kind.contains("UNIQUENESS") || kind.contains("NODE_KEY"))
+            && names("labelsOrTypes").contains(&label)
+            && !props.is_empty()
+            && props.iter().all(|p| keys.contains(p))
+    })
+}
+
+/// Labels with an index whose first property is `prop`.
+pub fn indexed_labels(indexes: &[serde_json::Value], prop: &str) -> Vec<String> {
+    let mut labels: Vec<String> = indexes
+        .iter()
+        .filter(|index| index["properties"][0].as_str() == Some(prop))
+        .filter_map(|index| index["labelsOrTypes"].as_array())
+        .flatten()
+        .filter_map(serde_json::Value::as_str)
+        .map(String::from)
+        .collect();
+    labels.sort();
+    labels.dedup();
+    labels
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn indexed_labels_are_unique() {
+        let indexes = [
+            json!({"labelsOrTypes": ["Person"], "properties": ["name"]}),
+            json!({"labelsOrTypes": ["City"], "properties": ["name"]}),
+            json!({"labelsOrTypes": ["Person"], "properties": ["name", "age"]}),
+            json!({"labelsOrTypes": ["Pet"], "properties": ["age", "name"]}),
+        ];
+        assert_eq!(indexed_labels(&indexes, "name"), ["City", "Person"]);
+    }
+}
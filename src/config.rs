@@ -0,0 +1,283 @@
+use agcli::CommandRequest;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
+use std::collections::BTreeMap;
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+use crate::error::AppError;
+
+/// File name of the per-project config, searched from the working directory upwards.
+pub const PROJECT_FILE: &str = ".lowmain.json";
+
+/// On-disk layout shared by the user and project config files.
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+pub struct ConfigFile {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub default_profile: Option<String>,
+    #[serde(default)]
+    pub profiles: BTreeMap<String, Profile>,
+}
+
+/// A named set of connection settings. Passwords are never stored inline,
+/// only the env var or file they are read from.
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+pub struct Profile {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub uri: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub user: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub db: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub password_env: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub password_file: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ca_cert: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub default_limit: Option<usize>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub read_only: Option<bool>,
+}
+
+impl Profile {
+    /// Read the password from the configured source, if any.
+    pub fn password(&self) -> Result<Option<String>, AppError> {
+        if let Some(var) = &self.password_env {
+            return Ok(env::var(var).ok());
+        }
+        if let Some(path) = &self.password_file {
+            let raw = fs::read_to_string(path).map_err(|e| AppError::ConfigInvalid {
+                reason: format!("Cannot read password_file {path}: {e}"),
+            })?;
+            return Ok(Some(raw.trim_end_matches(['\r', '\n']).to_string()));
+        }
+        Ok(None)
+    }
+
+    /// JSON view of the profile that names the password source but never its value.
+    pub fn redacted_json(&self) -> Value {
+        let password_source = match (&self.password_env, &self.password_file) {
+            (Some(var), _) => json!({ "env": var, "available": env::var(var).is_ok() }),
+            (None, Some(path)) => json!({ "file": path, "available": Path::new(path).is_file() }),
+            (None, None) => Value::Null,
+        };
+        json!({
+            "uri": self.uri,
+            "user": self.user,
+            "db": self.db,
+            "password_source": password_source,
+            "ca_cert": self.ca_cert,
+            "default_limit": self.default_limit,
+            "read_only": self.read_only.unwrap_or(false),
+        })
+    }
+}
+
+/// Which config file a profile came from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Scope {
+    User,
+    Project,
+}
+
+impl Scope {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::User => "user",
+            Self::Project => "project",
+        }
+    }
+}
+
+/// The profile selected for the current invocation.
+#[derive(Debug, Clone)]
+pub struct ActiveProfile {
+    pub name: String,
+    pub scope: Scope,
+    pub profile: Profile,
+    /// Selected with --profile or LOWMAIN_PROFILE rather than as a default_profile.
+    pub explicit: bool,
+}
+
+/// User config path: LOWMAIN_CONFIG > $XDG_CONFIG_HOME/lowmain > ~/.config/lowmain.
+pub fn user_config_path() -> Option<PathBuf> {
+    if let Ok(path) = env::var("LOWMAIN_CONFIG") {
+        return Some(PathBuf::from(path));
+    }
+    let base = env::var("XDG_CONFIG_HOME")
+        .map(PathBuf::from)
+        .or_else(|_| env::var("HOME").map(|h| Path::new(&h).join(".config")))
+        .ok()?;
+    Some(base.join("lowmain").join("config.json"))
+}
+
+/// Nearest `.lowmain.json` in the working directory or any of its ancestors.
+pub fn project_config_path() -> Option<PathBuf> {
+    let cwd = env::current_dir().ok()?;
+    cwd.ancestors()
+        .map(|dir| dir.join(PROJECT_FILE))
+        .find(|p| p.is_file())
+}
+
+/// Path to write project profiles to: the existing project file, or one in the working directory.
+pub fn project_config_path_for_write() -> Result<PathBuf, AppError> {
+    if let Some(path) = project_config_path() {
+        return Ok(path);
+    }
+    env::current_dir()
+        .map(|d| d.join(PROJECT_FILE))
+        .map_err(|e| AppError::ConfigInvalid {
+            reason: format!("Cannot determine working directory: {e}"),
+        })
+}
+
+/// Load a config file; a missing file is an empty config.
+pub fn load(path: &Path) -> Result<ConfigFile, AppError> {
+    load_json(path)
+}
+
+/// Write a config file, creating parent directories as needed.
+pub fn save(path: &Path, config: &ConfigFile) -> Result<(), AppError> {
+    save_json(path, config)
+}
+
+/// Load any lowmain JSON file; a missing file is the type's default.
+pub fn load_json<T: DeserializeOwned + Default>(path: &Path) -> Result<T, AppError> {
+    match fs::read_to_string(path) {
+        Ok(raw) => serde_json::from_str(&raw).map_err(|e| AppError::ConfigInvalid {
+            reason: format!("{}: {e}", path.display()),
+        }),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(T::default()),
+        Err(e) => Err(AppError::ConfigInvalid {
+            reason: format!("{}: {e}", path.display()),
+        }),
+    }
+}
+
+/// Write any lowmain JSON file, creating parent directories as needed.
+pub fn save_json<T: Serialize>(path: &Path, value: &T) -> Result<(), AppError> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|e| AppError::ConfigInvalid {
+            reason: format!("{}: {e}", parent.display()),
+        })?;
+    }
+    let raw = serde_json::to_string_pretty(value).map_err(|e| AppError::ConfigInvalid {
+        reason: e.to_string(),
+    })?;
+    fs::write(path, raw + "\n").map_err(|e| AppError::ConfigInvalid {
+        reason: format!("{}: {e}", path.display()),
+    })
+}
+
+/// Load both config files: (user, project).
+pub fn load_all() -> Result<(ConfigFile, ConfigFile), AppError> {
+    let user = match user_config_path() {
+        Some(path) => load(&path)?,
+        None => ConfigFile::default(),
+    };
+    let project = match project_config_path() {
+        Some(path) => load(&path)?,
+        None => ConfigFile::default(),
+    };
+    Ok((user, project))
+}
+
+/// Look up a profile by name. Project profiles shadow user profiles.
+pub fn find_profile(user: &ConfigFile, project: &ConfigFile, name: &str) -> Option<ActiveProfile> {
+    project
+        .profiles
+        .get(name)
+        .map(|p| (Scope::Project, p))
+        .or_else(|| user.profiles.get(name).map(|p| (Scope::User, p)))
+        .map(|(scope, profile)| ActiveProfile {
+            name: name.to_string(),
+            scope,
+            profile: profile.clone(),
+            explicit: false,
+        })
+}
+
+/// Resolve the profile for this request: --profile > LOWMAIN_PROFILE > default_profile.
+///
+/// The config files are read once per invocation; later calls return the same
+/// profile, or the same error, so a broken file fails every command that
+/// depends on it rather than being skipped by some.
+pub fn active_profile(req: &CommandRequest<'_>) -> Result<Option<ActiveProfile>, AppError> {
+    static ACTIVE: OnceLock<Result<Option<ActiveProfile>, AppError>> = OnceLock::new();
+    ACTIVE.get_or_init(|| select_profile(req)).clone()
+}
+
+fn select_profile(req: &CommandRequest<'_>) -> Result<Option<ActiveProfile>, AppError> {
+    let (user, project) = load_all()?;
+    let explicit = req
+        .flag("profile")
+        .map(String::from)
+        .or_else(|| env::var("LOWMAIN_PROFILE").ok());
+    let (name, is_explicit) = match explicit {
+        Some(name) => (name, true),
+        None => match project.default_profile.clone().or_else(|| user.default_profile.clone()) {
+            Some(name) => (name, false),
+            None => return Ok(None),
+        },
+    };
+    let mut active = find_profile(&user, &project, &name).ok_or(AppError::ProfileNotFound { name })?;
+    active.explicit = is_explicit;
+    Ok(Some(active))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn project_profile_shadows_user_profile() {
+        let mut user = ConfigFile::default();
+        user.profiles.insert(
+            "dev".into(),
+            Profile {
+                uri: Some("bolt://user:7687".into()),
+                ..Profile::default()
+            },
+        );
+        let mut project = ConfigFile::default();
+        project.profiles.insert(
+            "dev".into(),
+            Profile {
+                uri: Some("bolt://project:7687".into()),
+                ..Profile::default()
+            },
+        );
+
+        let found = find_profile(&user, &project, "dev").unwrap();
+        assert_eq!(found.scope, Scope::Project);
+        assert_eq!(found.profile.uri.as_deref(), Some("bolt://project:7687"));
+        assert!(find_profile(&user, &project, "prod").is_none());
+    }
+
+    #[test]
+    fn redacted_json_never_contains_password() {
+        let profile = Profile {
+            password_env: Some("LOWMAIN_TEST_UNSET_PASSWORD_VAR".into()),
+            ..Profile::default()
+        };
+        let out = profile.redacted_json();
+        assert_eq!(out["password_source"]["env"], "LOWMAIN_TEST_UNSET_PASSWORD_VAR");
+        assert_eq!(out["password_source"]["available"], false);
+        assert!(out.get("password").is_none());
+    }
+
+    #[test]
+    fn config_file_round_trips() {
+        let raw = r#"{"default_profile":"dev","profiles":{"dev":{"uri":"bolt://h:7687","default_limit":25}}}"#;
+        let parsed: ConfigFile = serde_json::from_str(raw).unwrap();
+        assert_eq!(parsed.default_profile.as_deref(), Some("dev"));
+        assert_eq!(parsed.profiles["dev"].default_limit, Some(25));
+        let back = serde_json::to_value(&parsed).unwrap();
+        assert!(back["profiles"]["dev"].get("user").is_none());
+    }
+}
@@ -1,115 +1,562 @@
-#[allow(unused_imports)]
-use neo4rs::{Node, Path, Relation, Row, UnboundedRelation};
+use chrono::{DateTime, FixedOffset, NaiveDate, NaiveDateTime, NaiveTime};
+use neo4rs::{BoltType, DeError, Row};
+use serde::de::{
+    self, Deserialize, DeserializeSeed, Deserializer, EnumAccess, IgnoredAny, IntoDeserializer, MapAccess, SeqAccess,
+    VariantAccess, Visitor,
+};
 use serde_json::{Map, Value, json};
+use std::collections::{HashMap, HashSet};
+use std::fmt;
 
-/// Convert a Neo4j Row to a JSON Value using serde deserialization.
+use crate::params;
+
+/// Convert a Neo4j Row to a JSON object keyed by column. Nodes, relationships
+/// and paths get the same `_id`/`_labels`/`_type` envelopes as `node find`,
+/// including when nested in lists and maps.
 pub fn row_to_json(row: &Row) -> Value {
-    row.to::<Value>().unwrap_or(Value::Null)
+    let Ok(columns) = row.to_strict::<HashMap<String, IgnoredAny>>() else {
+        return row.to::<Value>().unwrap_or(Value::Null);
+    };
+    Value::Object(
+        columns
+            .keys()
+            .map(|column| (column.clone(), column_to_json(row, column).unwrap_or(Value::Null)))
+            .collect(),
+    )
 }
 
-/// Convert a Neo4j Node to a JSON Value.
-pub fn node_to_json(node: &Node) -> Value {
-    let mut map = Map::new();
-    map.insert("_id".to_string(), json!(node.id()));
-    map.insert("_labels".to_string(), json!(node.labels()));
+/// Convert one column of a row. Entities are read this way rather than as
+/// `neo4rs::Node` or `Relation`: rebuilding those re-decodes every property,
+/// which flattens durations and fails outright on points.
+pub fn column_to_json(row: &Row, column: &str) -> Result<Value, DeError> {
+    row.get::<Decoded>(column).map(|decoded| decoded.0)
+}
 
-    for key in node.keys() {
-        let val = node_field_to_json(node, key);
-        map.insert(key.to_string(), val);
+/// Convert a Bolt value to JSON. JSON-native types map directly; temporal,
+/// spatial, byte and non-finite float values use the same `$tag` form accepted
+/// by `--params`, so output can be fed back in unchanged.
+pub fn bolt_to_json(value: &BoltType) -> Value {
+    match value {
+        BoltType::Null(_) => Value::Null,
+        BoltType::Boolean(b) => json!(b.value),
+        BoltType::Integer(i) => json!(i.value),
+        BoltType::Float(f) if f.value.is_finite() => json!(f.value),
+        BoltType::Float(f) => json!({ "$float": f.value.to_string() }),
+        BoltType::String(s) => json!(s.value),
+        BoltType::Bytes(b) => json!({ "$bytes": params::encode_base64(&b.value) }),
+        BoltType::Date(d) => tagged("$date", NaiveDate::try_from(d).map(|d| d.to_string())),
+        BoltType::LocalTime(t) => json!({ "$localtime": format_time(&NaiveTime::from(t)) }),
+        BoltType::Time(t) => {
+            let (time, offset): (NaiveTime, FixedOffset) = t.into();
+            json!({ "$time": format!("{}{offset}", format_time(&time)) })
+        }
+        BoltType::LocalDateTime(dt) => tagged(
+            "$localdatetime",
+            NaiveDateTime::try_from(dt).map(|dt| format_local_datetime(&dt)),
+        ),
+        BoltType::DateTime(dt) => tagged(
+            "$datetime",
+            DateTime::<FixedOffset>::try_from(dt).map(|dt| dt.to_rfc3339()),
+        ),
+        BoltType::DateTimeZoneId(dt) => tagged(
+            "$datetime",
+            DateTime::<FixedOffset>::try_from(dt)
+                .map(|local| format!("{}[{}]", format_local_datetime(&local.naive_local()), dt.tz_id())),
+        ),
+        // Lists, maps, entities, points and durations.
+        _ => Decoded::deserialize(value.into_deserializer())
+            .map(|decoded| decoded.0)
+            .unwrap_or(Value::Null),
     }
+}
 
+fn tagged(tag: &str, value: Result<String, neo4rs::Error>) -> Value {
+    let mut map = Map::new();
+    map.insert(tag.to_string(), value.map(Value::String).unwrap_or(Value::Null));
     Value::Object(map)
 }
 
-/// Extract a single property from a Node.
-fn node_field_to_json(node: &Node, key: &str) -> Value {
-    if let Ok(v) = node.get::<i64>(key) {
-        return json!(v);
+fn format_time(time: &NaiveTime) -> String {
+    time.format("%H:%M:%S%.f").to_string()
+}
+
+fn format_local_datetime(dt: &NaiveDateTime) -> String {
+    dt.format("%Y-%m-%dT%H:%M:%S%.f").to_string()
+}
+
+/// A Bolt value decoded straight to JSON.
+///
+/// The driver's serde support names each value's kind before handing out its
+/// contents. Lists, maps, entities, points and durations are decoded here, so
+/// values nested in them keep their kind; other kinds are handed back to
+/// `BoltType` and converted by [`bolt_to_json`]. The kind names are those of
+/// neo4rs 0.8.0, which Cargo.toml pins exactly.
+///
+/// neo4rs 0.8 only exposes a duration as total seconds and nanoseconds, with
+/// a month counted as 2629800 s and a day as 86400 s. Durations are therefore
+/// reported as `{"$duration": {"seconds": .., "nanos": ..}}`; their months and
+/// days cannot be recovered.
+struct Decoded(Value);
+
+impl<'de> Deserialize<'de> for Decoded {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_enum(std::any::type_name::<BoltType>(), &[], DecodedVisitor)
     }
-    if let Ok(v) = node.get::<f64>(key) {
-        return json!(v);
+}
+
+struct DecodedVisitor;
+
+impl<'de> Visitor<'de> for DecodedVisitor {
+    type Value = Decoded;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("a Bolt value")
     }
-    if let Ok(v) = node.get::<bool>(key) {
-        return json!(v);
+
+    fn visit_enum<A: EnumAccess<'de>>(self, data: A) -> Result<Self::Value, A::Error> {
+        let (Name(kind), variant) = data.variant()?;
+        let value = match kind.as_str() {
+            "list" => variant.tuple_variant(1, ListVisitor)?,
+            "map" => variant.tuple_variant(1, MapVisitor)?,
+            "duration" => variant.tuple_variant(1, DurationVisitor)?,
+            "point2_d" => variant.tuple_variant(3, PointVisitor { three_d: false })?,
+            "point3_d" => variant.tuple_variant(4, PointVisitor { three_d: true })?,
+            "path" => variant.tuple_variant(1, EntityVisitor)?.into_path(),
+            "node" | "relation" | "unbounded_relation" => variant.tuple_variant(1, EntityVisitor)?.into_envelope(),
+            _ => bolt_to_json(&BoltType::deserialize(Replay { kind, variant })?),
+        };
+        Ok(Decoded(value))
     }
-    if let Ok(v) = node.get::<String>(key) {
-        return json!(v);
+}
+
+/// A kind or field name, as the identifier the driver hands out.
+struct Name(String);
+
+impl<'de> Deserialize<'de> for Name {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_identifier(NameVisitor)
     }
-    if let Ok(v) = node.get::<Vec<String>>(key) {
-        return json!(v);
+}
+
+struct NameVisitor;
+
+impl<'de> Visitor<'de> for NameVisitor {
+    type Value = Name;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("an identifier")
     }
-    if let Ok(v) = node.get::<Vec<i64>>(key) {
-        return json!(v);
+
+    fn visit_str<E: de::Error>(self, v: &str) -> Result<Self::Value, E> {
+        Ok(Name(v.to_string()))
     }
-    Value::Null
 }
 
-/// Convert a Neo4j Relation to a JSON Value.
-pub fn relation_to_json(rel: &Relation) -> Value {
-    let mut map = Map::new();
-    map.insert("_id".to_string(), json!(rel.id()));
-    map.insert("_start_node_id".to_string(), json!(rel.start_node_id()));
-    map.insert("_end_node_id".to_string(), json!(rel.end_node_id()));
-    map.insert("_type".to_string(), json!(rel.typ()));
+/// A value whose kind was already read, handed back to `BoltType`'s own
+/// deserializer for the kinds [`Decoded`] does not decode itself.
+struct Replay<A> {
+    kind: String,
+    variant: A,
+}
+
+impl<'de, A: VariantAccess<'de>> Deserializer<'de> for Replay<A> {
+    type Error = A::Error;
 
-    for key in rel.keys() {
-        let val = rel_field_to_json(rel, key);
-        map.insert(key.to_string(), val);
+    fn deserialize_any<W: Visitor<'de>>(self, visitor: W) -> Result<W::Value, Self::Error> {
+        visitor.visit_enum(self)
     }
 
-    Value::Object(map)
+    serde::forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+        bytes byte_buf option unit unit_struct newtype_struct seq tuple
+        tuple_struct map struct enum identifier ignored_any
+    }
 }
 
-/// Extract a single property from a Relation.
-fn rel_field_to_json(rel: &Relation, key: &str) -> Value {
-    if let Ok(v) = rel.get::<i64>(key) {
-        return json!(v);
+impl<'de, A: VariantAccess<'de>> EnumAccess<'de> for Replay<A> {
+    type Error = A::Error;
+    type Variant = A;
+
+    fn variant_seed<S: DeserializeSeed<'de>>(self, seed: S) -> Result<(S::Value, A), A::Error> {
+        let kind = seed.deserialize(IntoDeserializer::<A::Error>::into_deserializer(self.kind))?;
+        Ok((kind, self.variant))
     }
-    if let Ok(v) = rel.get::<f64>(key) {
-        return json!(v);
+}
+
+struct ListVisitor;
+
+impl<'de> Visitor<'de> for ListVisitor {
+    type Value = Value;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("a Bolt list")
     }
-    if let Ok(v) = rel.get::<bool>(key) {
-        return json!(v);
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+        let mut items = Vec::new();
+        while let Some(Decoded(item)) = seq.next_element()? {
+            items.push(item);
+        }
+        Ok(Value::Array(items))
     }
-    if let Ok(v) = rel.get::<String>(key) {
-        return json!(v);
+}
+
+struct MapVisitor;
+
+impl<'de> Visitor<'de> for MapVisitor {
+    type Value = Value;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("a Bolt map")
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Self::Value, A::Error> {
+        let mut out = Map::new();
+        while let Some((key, Decoded(value))) = map.next_entry::<String, Decoded>()? {
+            out.insert(key, value);
+        }
+        Ok(Value::Object(out))
     }
-    Value::Null
 }
 
-/// Convert an UnboundedRelation to a JSON Value.
-#[allow(dead_code)]
-fn unbounded_rel_to_json(rel: &UnboundedRelation) -> Value {
-    let mut map = Map::new();
-    map.insert("_id".to_string(), json!(rel.id()));
-    map.insert("_type".to_string(), json!(rel.typ()));
-
-    for key in rel.keys() {
-        let val = if let Ok(v) = rel.get::<String>(key) {
-            json!(v)
-        } else if let Ok(v) = rel.get::<i64>(key) {
-            json!(v)
-        } else if let Ok(v) = rel.get::<f64>(key) {
-            json!(v)
-        } else if let Ok(v) = rel.get::<bool>(key) {
-            json!(v)
-        } else {
-            Value::Null
+struct DurationVisitor;
+
+impl<'de> Visitor<'de> for DurationVisitor {
+    type Value = Value;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("a Bolt duration as seconds and nanoseconds")
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+        let seconds: i64 = seq.next_element()?.ok_or_else(|| de::Error::invalid_length(0, &self))?;
+        let nanos: i64 = seq.next_element()?.ok_or_else(|| de::Error::invalid_length(1, &self))?;
+        Ok(json!({ "$duration": { "seconds": seconds, "nanos": nanos } }))
+    }
+}
+
+struct PointVisitor {
+    three_d: bool,
+}
+
+impl<'de> Visitor<'de> for PointVisitor {
+    type Value = Value;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("a Bolt point as srid and coordinates")
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+        let srid: i64 = seq.next_element()?.ok_or_else(|| de::Error::invalid_length(0, &self))?;
+        let x: f64 = seq.next_element()?.ok_or_else(|| de::Error::invalid_length(1, &self))?;
+        let y: f64 = seq.next_element()?.ok_or_else(|| de::Error::invalid_length(2, &self))?;
+        if !self.three_d {
+            return Ok(json!({ "$point": { "srid": srid, "x": x, "y": y } }));
+        }
+        let z: f64 = seq.next_element()?.ok_or_else(|| de::Error::invalid_length(3, &self))?;
+        Ok(json!({ "$point": { "srid": srid, "x": x, "y": y, "z": z } }))
+    }
+}
+
+/// The fields of a node, relationship or path.
+#[derive(Default)]
+struct Entity {
+    envelope: Map<String, Value>,
+    properties: Map<String, Value>,
+    nodes: Vec<Value>,
+    relationships: Vec<Value>,
+    indices: Vec<i64>,
+}
+
+impl Entity {
+    /// A node or relationship: its `_`-prefixed envelope plus its properties.
+    fn into_envelope(self) -> Value {
+        let mut map = self.envelope;
+        map.extend(self.properties);
+        Value::Object(map)
+    }
+
+    /// A path, listing nodes and relationships in traversal order. Bolt sends
+    /// each distinct node/relationship once plus an index walk; relationships
+    /// get their direction back from that walk.
+    fn into_path(self) -> Value {
+        let (nodes, rels) = (self.nodes, self.relationships);
+        let Some(mut current) = nodes.first() else {
+            return json!({ "_type": "path", "nodes": [], "relationships": [], "length": 0 });
         };
-        map.insert(key.to_string(), val);
+
+        let mut walk_nodes = vec![current.clone()];
+        let mut walk_rels = Vec::new();
+        for step in self.indices.chunks_exact(2) {
+            let (rel_index, node_index) = (step[0], step[1]);
+            // Relationship indices are 1-based and signed by direction; 0 is malformed.
+            let rel = (rel_index.unsigned_abs() as usize).checked_sub(1).and_then(|i| rels.get(i));
+            let next = usize::try_from(node_index).ok().and_then(|i| nodes.get(i));
+            let (Some(rel), Some(next)) = (rel, next) else {
+                break;
+            };
+            let (start, end) = if rel_index > 0 { (current, next) } else { (next, current) };
+            let mut rel_json = rel.clone();
+            if let Value::Object(map) = &mut rel_json {
+                map.insert("_start_node_id".to_string(), start["_id"].clone());
+                map.insert("_end_node_id".to_string(), end["_id"].clone());
+            }
+            walk_rels.push(rel_json);
+            walk_nodes.push(next.clone());
+            current = next;
+        }
+
+        json!({
+            "_type": "path",
+            "length": walk_rels.len(),
+            "nodes": walk_nodes,
+            "relationships": walk_rels,
+        })
     }
+}
 
-    Value::Object(map)
+struct EntityVisitor;
+
+impl<'de> Visitor<'de> for EntityVisitor {
+    type Value = Entity;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("a Bolt node, relationship or path")
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Self::Value, A::Error> {
+        let mut entity = Entity::default();
+        while let Some(Name(key)) = map.next_key()? {
+            match key.as_str() {
+                "id" | "start_node_id" | "end_node_id" => {
+                    entity.envelope.insert(format!("_{key}"), json!(map.next_value::<i64>()?));
+                }
+                "type" => {
+                    entity.envelope.insert("_type".to_string(), json!(map.next_value::<String>()?));
+                }
+                "labels" => {
+                    entity.envelope.insert("_labels".to_string(), json!(map.next_value::<Vec<String>>()?));
+                }
+                "properties" => {
+                    let properties: HashMap<String, Decoded> = map.next_value()?;
+                    entity.properties = properties.into_iter().map(|(k, v)| (k, v.0)).collect();
+                }
+                "nodes" => entity.nodes = decoded_list(map.next_value::<Vec<Decoded>>()?),
+                "relationships" => entity.relationships = decoded_list(map.next_value::<Vec<Decoded>>()?),
+                "indices" => entity.indices = map.next_value()?,
+                _ => {
+                    map.next_value::<IgnoredAny>()?;
+                }
+            }
+        }
+        Ok(entity)
+    }
+}
+
+fn decoded_list(items: Vec<Decoded>) -> Vec<Value> {
+    items.into_iter().map(|item| item.0).collect()
 }
 
-/// Convert a Neo4j Path to a JSON Value.
-#[allow(dead_code)]
-fn path_to_json(path: &Path) -> Value {
-    let nodes: Vec<Value> = path.nodes().iter().map(node_to_json).collect();
-    let rels: Vec<Value> = path.rels().iter().map(unbounded_rel_to_json).collect();
+/// Collect every node and relationship envelope in converted rows into a single
+/// `{nodes, relationships}` graph, each entity once (first occurrence wins).
+pub fn rows_to_graph(rows: &[Value]) -> Value {
+    let mut graph = GraphCollector::default();
+    for row in rows {
+        graph.visit(row);
+    }
     json!({
-        "_type": "path",
-        "nodes": nodes,
-        "relationships": rels,
+        "nodes": graph.nodes,
+        "relationships": graph.relationships,
     })
 }
+
+#[derive(Default)]
+struct GraphCollector {
+    nodes: Vec<Value>,
+    relationships: Vec<Value>,
+    node_ids: HashSet<i64>,
+    relationship_ids: HashSet<i64>,
+}
+
+impl GraphCollector {
+    fn visit(&mut self, value: &Value) {
+        match value {
+            Value::Array(items) => items.iter().for_each(|v| self.visit(v)),
+            Value::Object(map) if map.get("_type").and_then(Value::as_str) == Some("path") => {
+                for key in ["nodes", "relationships"] {
+                    if let Some(items) = map.get(key) {
+                        self.visit(items);
+                    }
+                }
+            }
+            Value::Object(map) => match map.get("_id").and_then(Value::as_i64) {
+                Some(id) if map.contains_key("_labels") => {
+                    if self.node_ids.insert(id) {
+                        self.nodes.push(value.clone());
+                    }
+                }
+                // Only relationships with endpoints can be drawn; path segments get them from the walk.
+                Some(id) if map.contains_key("_start_node_id") => {
+                    if self.relationship_ids.insert(id) {
+                        self.relationships.push(value.clone());
+                    }
+                }
+                _ => map.values().for_each(|v| self.visit(v)),
+            },
+            _ => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use neo4rs::{
+        BoltBytes, BoltFloat, BoltInteger, BoltList, BoltMap, BoltNode, BoltPath, BoltPoint2D, BoltString,
+        BoltUnboundedRelation,
+    };
+
+    fn node_with(key: &str, value: BoltType) -> Value {
+        let mut props = BoltMap::new();
+        props.put(BoltString::new(key), value);
+        let node = BoltNode::new(BoltInteger::new(1), BoltList::from(vec![BoltType::from("Event")]), props);
+        let row = Row::new(BoltList::from(vec![BoltType::from("n")]), BoltList::from(vec![BoltType::Node(node)]));
+        column_to_json(&row, "n").unwrap()
+    }
+
+    #[test]
+    fn typed_params_round_trip_through_node_properties() {
+        for literal in [
+            json!({"$date": "2024-01-02"}),
+            json!({"$localtime": "12:30:00"}),
+            json!({"$time": "12:30:00+01:00"}),
+            json!({"$localdatetime": "2024-01-02T03:04:05.250"}),
+            json!({"$datetime": "2024-01-02T03:04:05+02:00"}),
+            json!({"$datetime": "2024-01-02T03:04:05[Europe/Berlin]"}),
+            json!({"$duration": {"seconds": 90, "nanos": 500_000_000}}),
+            json!({"$bytes": "aGVsbG8="}),
+            json!({"$float": "NaN"}),
+            json!({"$float": "-inf"}),
+            json!({"$point": {"srid": 7203, "x": 1.0, "y": 2.0}}),
+            json!({"$point": {"srid": 4979, "x": 1.0, "y": 2.0, "z": 3.0}}),
+        ] {
+            let bolt = params::json_to_bolt(&literal).unwrap();
+            assert_eq!(node_with("v", bolt)["v"], literal);
+        }
+    }
+
+    #[test]
+    fn lists_and_floats_are_not_null() {
+        let list = BoltType::List(BoltList::from(vec![
+            BoltType::Float(BoltFloat::new(1.5)),
+            BoltType::from(true),
+        ]));
+        assert_eq!(node_with("v", list)["v"], json!([1.5, true]));
+
+        let point = BoltType::Point2D(BoltPoint2D {
+            sr_id: BoltInteger::new(4326),
+            x: BoltFloat::new(12.5),
+            y: BoltFloat::new(55.6),
+        });
+        assert_eq!(bolt_to_json(&point)["$point"]["srid"], 4326);
+        assert_eq!(node_with("at", point)["at"]["$point"]["x"], 12.5);
+        assert_eq!(
+            bolt_to_json(&BoltType::Bytes(BoltBytes::new(vec![0u8, 255].into()))),
+            json!({"$bytes": "AP8="})
+        );
+    }
+
+    #[test]
+    fn durations_fold_calendar_units_into_seconds_at_any_depth() {
+        let duration = |text: &str| params::json_to_bolt(&json!({ "$duration": text })).unwrap();
+        let list = BoltType::List(BoltList::from(vec![duration("P1M"), duration("P2DT1.5S")]));
+        assert_eq!(
+            node_with("spans", list.clone())["spans"],
+            json!([
+                {"$duration": {"seconds": 2_629_800, "nanos": 0}},
+                {"$duration": {"seconds": 172_801, "nanos": 500_000_000}}
+            ])
+        );
+
+        let mut nested = BoltMap::new();
+        nested.put(BoltString::new("grace \"period\""), duration("-P1D"));
+        let row = Row::new(
+            BoltList::from(vec![BoltType::from("d"), BoltType::from("m"), BoltType::from("n")]),
+            BoltList::from(vec![duration("PT1H"), BoltType::Map(nested), BoltType::List(BoltList::from(vec![list]))]),
+        );
+        let out = row_to_json(&row);
+        assert_eq!(out["d"], json!({"$duration": {"seconds": 3600, "nanos": 0}}));
+        assert_eq!(out["m"]["grace \"period\""], json!({"$duration": {"seconds": -86_400, "nanos": 0}}));
+        assert_eq!(out["n"][0][1]["$duration"]["seconds"], 172_801);
+    }
+
+    fn node(id: i64, label: &str) -> BoltType {
+        let mut props = BoltMap::new();
+        props.put(BoltString::new("name"), BoltType::from(label.to_lowercase()));
+        BoltType::Node(BoltNode::new(
+            BoltInteger::new(id),
+            BoltList::from(vec![BoltType::from(label)]),
+            props,
+        ))
+    }
+
+    #[test]
+    fn rows_keep_graph_envelopes_in_nested_values() {
+        let knows = BoltType::UnboundedRelation(BoltUnboundedRelation::new(
+            BoltInteger::new(7),
+            BoltString::new("KNOWS"),
+            BoltMap::new(),
+        ));
+        // (1)<-[:KNOWS]-(2): the walk steps backwards over relationship 1 to node index 1.
+        let path = BoltType::Path(BoltPath {
+            nodes: BoltList::from(vec![node(1, "Person"), node(2, "Person")]),
+            rels: BoltList::from(vec![knows]),
+            indices: BoltList::from(vec![BoltType::from(-1_i64), BoltType::from(1_i64)]),
+        });
+        let row = Row::new(
+            BoltList::from(vec![BoltType::from("p"), BoltType::from("people")]),
+            BoltList::from(vec![path, BoltType::List(BoltList::from(vec![node(3, "Team")]))]),
+        );
+
+        let out = row_to_json(&row);
+        assert_eq!(out["p"]["_type"], "path");
+        assert_eq!(out["p"]["length"], 1);
+        assert_eq!(out["p"]["nodes"][1]["_id"], 2);
+        assert_eq!(out["p"]["relationships"][0]["_type"], "KNOWS");
+        assert_eq!(out["p"]["relationships"][0]["_start_node_id"], 2);
+        assert_eq!(out["p"]["relationships"][0]["_end_node_id"], 1);
+        assert_eq!(out["people"][0]["_labels"], json!(["Team"]));
+        assert_eq!(out["people"][0]["name"], "team");
+    }
+
+    #[test]
+    fn malformed_path_indices_end_the_walk() {
+        let knows = BoltType::UnboundedRelation(BoltUnboundedRelation::new(
+            BoltInteger::new(7),
+            BoltString::new("KNOWS"),
+            BoltMap::new(),
+        ));
+        let path = BoltType::Path(BoltPath {
+            nodes: BoltList::from(vec![node(1, "Person"), node(2, "Person")]),
+            rels: BoltList::from(vec![knows]),
+            indices: BoltList::from(vec![BoltType::from(0_i64), BoltType::from(1_i64)]),
+        });
+        let out = bolt_to_json(&path);
+        assert_eq!(out["length"], 0);
+        assert_eq!(out["nodes"].as_array().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn graph_shape_deduplicates_across_rows() {
+        let rows = vec![
+            json!({"a": {"_id": 1, "_labels": ["P"]}, "r": {"_id": 9, "_type": "KNOWS", "_start_node_id": 1, "_end_node_id": 2}}),
+            json!({"a": {"_id": 1, "_labels": ["P"]}, "m": {"nested": [{"_id": 2, "_labels": ["P"]}]}}),
+            json!({"p": {"_type": "path", "nodes": [{"_id": 2, "_labels": ["P"]}], "relationships": [
+                {"_id": 9, "_type": "KNOWS", "_start_node_id": 1, "_end_node_id": 2}
+            ]}}),
+        ];
+        let graph = rows_to_graph(&rows);
+        assert_eq!(graph["nodes"].as_array().unwrap().len(), 2);
+        assert_eq!(graph["relationships"].as_array().unwrap().len(), 1);
+    }
+}
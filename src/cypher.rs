@@ -0,0 +1,536 @@
+//! Lightweight Cypher lexing: enough to classify statements without a full
+//! parser. String literals, comments and backquoted names are tokenized as
+//! opaque units so their contents never count as keywords.
+
+/// A lexical token of a Cypher statement.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Token {
+    /// Identifier or keyword, as written.
+    Word(String),
+    /// Backquoted name, unescaped.
+    Quoted(String),
+    /// `$name` parameter reference.
+    Param(String),
+    /// String or number literal.
+    Literal,
+    /// Any other single character.
+    Symbol(char),
+}
+
+/// Split Cypher into tokens, dropping whitespace and comments.
+pub fn tokenize(cypher: &str) -> Vec<Token> {
+    let chars: Vec<char> = cypher.chars().collect();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+
+    while i < chars.len() {
+        let c = chars[i];
+        match c {
+            c if c.is_whitespace() => i += 1,
+            '/' if chars.get(i + 1) == Some(&'/') => {
+                while i < chars.len() && chars[i] != '\n' {
+                    i += 1;
+                }
+            }
+            '/' if chars.get(i + 1) == Some(&'*') => {
+                i += 2;
+                while i < chars.len() && !(chars[i] == '*' && chars.get(i + 1) == Some(&'/')) {
+                    i += 1;
+                }
+                i += 2;
+            }
+            '\'' | '"' => {
+                i += 1;
+                while i < chars.len() && chars[i] != c {
+                    i += if chars[i] == '\\' { 2 } else { 1 };
+                }
+                i += 1;
+                tokens.push(Token::Literal);
+            }
+            '`' => {
+                let (name, end) = backquoted(&chars, i);
+                tokens.push(Token::Quoted(name));
+                i = end;
+            }
+            '$' => {
+                i += 1;
+                if chars.get(i) == Some(&'`') {
+                    let (name, end) = backquoted(&chars, i);
+                    tokens.push(Token::Param(name));
+                    i = end;
+                } else {
+                    let start = i;
+                    while i < chars.len() && is_word_char(chars[i]) {
+                        i += 1;
+                    }
+                    tokens.push(Token::Param(chars[start..i].iter().collect()));
+                }
+            }
+            c if c.is_ascii_digit() => {
+                while i < chars.len() && (is_word_char(chars[i]) || chars[i] == '.') {
+                    // Stop at `..` so `1..3` in variable-length patterns stays three tokens.
+                    if chars[i] == '.' && chars.get(i + 1) == Some(&'.') {
+                        break;
+                    }
+                    i += 1;
+                }
+                tokens.push(Token::Literal);
+            }
+            c if c.is_alphabetic() || c == '_' => {
+                let start = i;
+                while i < chars.len() && is_word_char(chars[i]) {
+                    i += 1;
+                }
+                tokens.push(Token::Word(chars[start..i].iter().collect()));
+            }
+            c => {
+                tokens.push(Token::Symbol(c));
+                i += 1;
+            }
+        }
+    }
+    tokens
+}
+
+fn is_word_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_'
+}
+
+/// Read a backquoted name starting at `start`; doubled backquotes are escapes.
+fn backquoted(chars: &[char], start: usize) -> (String, usize) {
+    let mut name = String::new();
+    let mut i = start + 1;
+    while i < chars.len() {
+        if chars[i] == '`' {
+            if chars.get(i + 1) == Some(&'`') {
+                name.push('`');
+                i += 2;
+                continue;
+            }
+            return (name, i + 1);
+        }
+        name.push(chars[i]);
+        i += 1;
+    }
+    (name, i)
+}
+
+/// One statement of a multi-statement script.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Statement {
+    /// 1-based line of the script the statement starts on.
+    pub line: usize,
+    pub text: String,
+}
+
+/// Split a script on top-level `;`. Semicolons inside strings, comments and
+/// backquoted names do not end a statement; empty statements are dropped.
+pub fn split_statements(script: &str) -> Vec<Statement> {
+    let chars: Vec<char> = script.chars().collect();
+    let mut pieces = Vec::new();
+    let mut start = 0;
+    let mut i = 0;
+
+    while i < chars.len() {
+        let c = chars[i];
+        match c {
+            '/' if chars.get(i + 1) == Some(&'/') => {
+                while i < chars.len() && chars[i] != '\n' {
+                    i += 1;
+                }
+            }
+            '/' if chars.get(i + 1) == Some(&'*') => {
+                i += 2;
+                while i < chars.len() && !(chars[i] == '*' && chars.get(i + 1) == Some(&'/')) {
+                    i += 1;
+                }
+                i += 2;
+            }
+            '\'' | '"' | '`' => {
+                i += 1;
+                while i < chars.len() && chars[i] != c {
+                    i += if chars[i] == '\\' && c != '`' { 2 } else { 1 };
+                }
+                i += 1;
+            }
+            ';' => {
+                pieces.push((start, i));
+                start = i + 1;
+                i += 1;
+            }
+            _ => i += 1,
+        }
+    }
+    pieces.push((start, chars.len()));
+
+    pieces
+        .into_iter()
+        .filter_map(|(from, to)| {
+            let text: String = chars[from..to.min(chars.len())].iter().collect();
+            if tokenize(&text).is_empty() {
+                return None;
+            }
+            let leading = text.len() - text.trim_start().len();
+            let line = 1 + chars[..from].iter().filter(|&&c| c == '\n').count()
+                + text[..leading].matches('\n').count();
+            Some(Statement {
+                line,
+                text: text.trim().to_string(),
+            })
+        })
+        .collect()
+}
+
+/// Upper-cased words outside name positions (keywords, plus variables and
+/// function names).
+pub fn keywords(tokens: &[Token]) -> Vec<String> {
+    tokens
+        .iter()
+        .enumerate()
+        .filter_map(|(i, token)| match token {
+            Token::Word(word) if !is_name(tokens, i) => Some(word.to_uppercase()),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Whether the word at `i` is a label, relationship type, property key or map
+/// key. Those are names even when they spell a keyword (`n.set`, `:Create`,
+/// `{delete: 1}`).
+pub fn is_name(tokens: &[Token], i: usize) -> bool {
+    let prev = i.checked_sub(1).and_then(|p| tokens.get(p));
+    let next = tokens.get(i + 1);
+    matches!(prev, Some(Token::Symbol('.' | ':')))
+        || is_label_alternative(tokens, i)
+        || (matches!(next, Some(Token::Symbol(':'))) && matches!(prev, Some(Token::Symbol('{' | ','))))
+}
+
+/// Whether the token at `i` continues a label expression such as `:A|B` or
+/// `[:A|B&C]`. A `|` elsewhere (`FOREACH (x IN xs | CREATE ...)`, list
+/// comprehensions) is followed by an ordinary clause or expression.
+fn is_label_alternative(tokens: &[Token], i: usize) -> bool {
+    if i < 2 || !matches!(tokens[i - 1], Token::Symbol('|' | '&')) {
+        return false;
+    }
+    match tokens[i - 2] {
+        Token::Word(_) | Token::Quoted(_) => {
+            matches!(tokens.get(i - 3), Some(Token::Symbol(':'))) || is_label_alternative(tokens, i - 2)
+        }
+        _ => false,
+    }
+}
+
+/// Statement category, mirroring the server's `type` summary field.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum QueryType {
+    Read,
+    Write,
+    ReadWrite,
+    Schema,
+}
+
+impl QueryType {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Read => "r",
+            Self::Write => "w",
+            Self::ReadWrite => "rw",
+            Self::Schema => "s",
+        }
+    }
+}
+
+const WRITE_KEYWORDS: [&str; 6] = ["CREATE", "MERGE", "DELETE", "DETACH", "SET", "REMOVE"];
+const READ_KEYWORDS: [&str; 5] = ["MATCH", "MERGE", "RETURN", "CALL", "SHOW"];
+const SCHEMA_OBJECTS: [&str; 6] = ["INDEX", "CONSTRAINT", "DATABASE", "USER", "ROLE", "ALIAS"];
+const ADMIN_KEYWORDS: [&str; 4] = ["GRANT", "REVOKE", "DENY", "ALTER"];
+/// Administration commands recognised by their first two keywords.
+const ADMIN_COMMANDS: [(&str, &[&str]); 7] = [
+    ("START", &["DATABASE"]),
+    ("STOP", &["DATABASE"]),
+    ("TERMINATE", &["TRANSACTION", "TRANSACTIONS"]),
+    ("ENABLE", &["SERVER"]),
+    ("DEALLOCATE", &["DATABASE", "DATABASES"]),
+    ("REALLOCATE", &["DATABASE", "DATABASES"]),
+    ("RENAME", &["USER", "ROLE", "SERVER"]),
+];
+
+/// Classify a statement from its keywords.
+pub fn query_type(cypher: &str) -> QueryType {
+    let kws = keywords(&tokenize(cypher));
+    let has = |set: &[&str]| kws.iter().any(|k| set.contains(&k.as_str()));
+
+    let defines_schema = kws.iter().enumerate().any(|(i, k)| {
+        (k == "CREATE" || k == "DROP")
+            && kws[i + 1..]
+                .iter()
+                .take(3)
+                .any(|next| SCHEMA_OBJECTS.contains(&next.as_str()))
+    });
+    let administers = kws.windows(2).any(|pair| {
+        ADMIN_COMMANDS
+            .iter()
+            .any(|(command, objects)| pair[0] == *command && objects.contains(&pair[1].as_str()))
+    });
+    if defines_schema || administers || has(&ADMIN_KEYWORDS) {
+        return QueryType::Schema;
+    }
+
+    match (has(&WRITE_KEYWORDS), has(&READ_KEYWORDS)) {
+        (true, true) => QueryType::ReadWrite,
+        (true, false) => QueryType::Write,
+        _ => QueryType::Read,
+    }
+}
+
+/// Procedures known not to modify the database. Entries ending in `.` match a
+/// whole namespace; anything else called with `CALL` may write.
+const READ_ONLY_PROCEDURES: [&str; 17] = [
+    "db.labels",
+    "db.relationshiptypes",
+    "db.propertykeys",
+    "db.schema.",
+    "db.indexes",
+    "db.constraints",
+    "db.info",
+    "db.ping",
+    "db.awaitindexes",
+    "dbms.components",
+    "dbms.procedures",
+    "dbms.functions",
+    "dbms.showcurrentuser",
+    "dbms.listconfig",
+    "apoc.meta.",
+    "apoc.help",
+    "apoc.version",
+];
+
+/// Names of the procedures a statement calls, lower-cased. `CALL { ... }`
+/// subqueries are not procedures and are skipped.
+pub fn procedures(tokens: &[Token]) -> Vec<String> {
+    let mut names = Vec::new();
+    for (i, token) in tokens.iter().enumerate() {
+        if !matches!(token, Token::Word(w) if w.eq_ignore_ascii_case("CALL")) || is_name(tokens, i) {
+            continue;
+        }
+        let mut parts = Vec::new();
+        let mut j = i + 1;
+        while let Some(Token::Word(part) | Token::Quoted(part)) = tokens.get(j) {
+            parts.push(part.to_lowercase());
+            if tokens.get(j + 1) != Some(&Token::Symbol('.')) {
+                break;
+            }
+            j += 2;
+        }
+        if !parts.is_empty() {
+            names.push(parts.join("."));
+        }
+    }
+    names
+}
+
+fn is_read_only_procedure(name: &str) -> bool {
+    READ_ONLY_PROCEDURES.iter().any(|known| match known.strip_suffix('.') {
+        Some(namespace) => name.strip_prefix(namespace).is_some_and(|rest| rest.starts_with('.')),
+        None => name == *known,
+    })
+}
+
+/// Whether the statement's result is sorted: an `ORDER BY` follows its last
+/// top-level `RETURN`. Sorting inside subqueries, comprehensions or a `WITH`
+/// does not order the rows returned, and neither does sorting one `UNION` arm.
+pub fn orders_result(cypher: &str) -> bool {
+    final_order_by(&tokenize(cypher)).is_some()
+}
+
+/// Index of the `ORDER` token that sorts the result, per [`orders_result`].
+fn final_order_by(tokens: &[Token]) -> Option<usize> {
+    let mut depth = 0usize;
+    let mut after_return = false;
+    let mut ordered = None;
+    for (i, token) in tokens.iter().enumerate() {
+        match token {
+            Token::Symbol('(' | '[' | '{') => depth += 1,
+            Token::Symbol(')' | ']' | '}') => depth = depth.saturating_sub(1),
+            Token::Word(word) if depth == 0 && !is_name(tokens, i) => {
+                let next_is_by = matches!(tokens.get(i + 1), Some(Token::Word(w)) if w.eq_ignore_ascii_case("BY"));
+                if word.eq_ignore_ascii_case("RETURN") {
+                    after_return = true;
+                    ordered = None;
+                } else if word.eq_ignore_ascii_case("UNION") {
+                    return None;
+                } else if word.eq_ignore_ascii_case("ORDER") && next_is_by && after_return {
+                    ordered = Some(i);
+                }
+            }
+            _ => {}
+        }
+    }
+    ordered
+}
+
+/// The columns the result is sorted by, as `(name, descending)`, when its
+/// final `ORDER BY` only names columns: `ORDER BY name, age DESC`. `None` when
+/// the result is unsorted or a term is an expression such as `n.name`.
+pub fn result_order(cypher: &str) -> Option<Vec<(String, bool)>> {
+    let tokens = tokenize(cypher);
+    let mut i = final_order_by(&tokens)? + 2;
+    let mut terms = Vec::new();
+    loop {
+        let name = match tokens.get(i)? {
+            Token::Word(w) | Token::Quoted(w) => w.clone(),
+            _ => return None,
+        };
+        i += 1;
+        let mut descending = false;
+        if let Some(Token::Word(w)) = tokens.get(i) {
+            match w.to_ascii_uppercase().as_str() {
+                "ASC" | "ASCENDING" => i += 1,
+                "DESC" | "DESCENDING" => {
+                    descending = true;
+                    i += 1;
+                }
+                _ => {}
+            }
+        }
+        terms.push((name, descending));
+        match tokens.get(i) {
+            Some(Token::Symbol(',')) => i += 1,
+            None | Some(Token::Symbol(';')) => return Some(terms),
+            Some(Token::Word(w)) if ["SKIP", "OFFSET", "LIMIT"].iter().any(|k| w.eq_ignore_ascii_case(k)) => {
+                return Some(terms);
+            }
+            Some(_) => return None,
+        }
+    }
+}
+
+/// Why a statement may modify the database, or `None` when it only reads.
+pub fn write_reason(cypher: &str) -> Option<String> {
+    let tokens = tokenize(cypher);
+    match query_type(cypher) {
+        QueryType::Schema => {
+            return Some("the statement changes schema or administration settings".into());
+        }
+        QueryType::Write | QueryType::ReadWrite => {
+            let kws = keywords(&tokens);
+            let clause = match kws.iter().find(|k| WRITE_KEYWORDS.contains(&k.as_str())) {
+                Some(k) if k == "DETACH" => "DETACH DELETE",
+                Some(k) => k.as_str(),
+                None => "write",
+            };
+            return Some(format!("the statement contains a {clause} clause"));
+        }
+        QueryType::Read => {}
+    }
+    procedures(&tokens)
+        .into_iter()
+        .find(|name| !is_read_only_procedure(name))
+        .map(|name| format!("the statement calls {name}, which is not known to be read-only"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn strings_comments_and_names_are_not_keywords() {
+        let cypher = "MATCH (n:Create {delete: 'SET x'}) // CREATE\nWHERE n.merge = `REMOVE` RETURN n /* DELETE */";
+        assert_eq!(keywords(&tokenize(cypher)), ["MATCH", "N", "WHERE", "N", "RETURN", "N"]);
+        assert_eq!(query_type(cypher), QueryType::Read);
+    }
+
+    #[test]
+    fn classifies_like_the_server() {
+        assert_eq!(query_type("CREATE (n:Person)"), QueryType::Write);
+        assert_eq!(query_type("CREATE (n) RETURN n"), QueryType::ReadWrite);
+        assert_eq!(query_type("MERGE (n:A {id: 1})"), QueryType::ReadWrite);
+        assert_eq!(query_type("MATCH (n) DETACH DELETE n"), QueryType::ReadWrite);
+        assert_eq!(query_type("CREATE INDEX person_name FOR (n:Person) ON (n.name)"), QueryType::Schema);
+        assert_eq!(query_type("DROP CONSTRAINT c IF EXISTS"), QueryType::Schema);
+        assert_eq!(query_type("MATCH p=(a)-[*1..3]->(b) RETURN p"), QueryType::Read);
+    }
+
+    #[test]
+    fn tokenizes_params_and_backquotes() {
+        let tokens = tokenize("RETURN $id, $`odd name`, `a``b`");
+        assert!(tokens.contains(&Token::Param("id".into())));
+        assert!(tokens.contains(&Token::Param("odd name".into())));
+        assert!(tokens.contains(&Token::Quoted("a`b".into())));
+    }
+
+    #[test]
+    fn write_reason_flags_writes_and_unknown_procedures() {
+        assert_eq!(write_reason("MATCH (n) RETURN n.set"), None);
+        assert_eq!(
+            write_reason("MATCH (n) DETACH DELETE n").as_deref(),
+            Some("the statement contains a DETACH DELETE clause")
+        );
+        assert!(write_reason("DROP INDEX person_name").is_some());
+        assert_eq!(write_reason("CALL db.labels() YIELD label RETURN label"), None);
+        assert_eq!(write_reason("CALL db.schema.visualization()"), None);
+        assert_eq!(write_reason("CALL { MATCH (n) RETURN n } RETURN n"), None);
+        assert_eq!(
+            write_reason("CALL apoc.create.node(['A'], {})").as_deref(),
+            Some("the statement calls apoc.create.node, which is not known to be read-only")
+        );
+    }
+
+    #[test]
+    fn foreach_bodies_are_not_label_alternatives() {
+        for body in ["CREATE (:Junk)", "SET n.x = 1", "MERGE (:Junk {id: x})", "REMOVE n.x", "DELETE n"] {
+            let cypher = format!("MATCH (n) FOREACH (x IN range(1, 10) | {body})");
+            assert!(write_reason(&cypher).is_some(), "{cypher}");
+        }
+        assert_eq!(query_type("MATCH (n:Create|Delete)-[:SET|`MERGE`&REMOVE]->(m) RETURN n"), QueryType::Read);
+        assert_eq!(query_type("RETURN [x IN range(1, 3) | x * 2] AS xs"), QueryType::Read);
+    }
+
+    #[test]
+    fn database_and_transaction_administration_is_schema() {
+        assert_eq!(query_type("STOP DATABASE neo4j"), QueryType::Schema);
+        assert_eq!(query_type("START DATABASE neo4j WAIT"), QueryType::Schema);
+        assert_eq!(query_type("TERMINATE TRANSACTIONS 'neo4j-transaction-1'"), QueryType::Schema);
+        assert_eq!(query_type("ENABLE SERVER 'abc'"), QueryType::Schema);
+        assert!(write_reason("TERMINATE TRANSACTION 'neo4j-transaction-1'").is_some());
+        assert_eq!(query_type("MATCH (start)-->(stop) RETURN start, stop"), QueryType::Read);
+    }
+
+    #[test]
+    fn only_a_top_level_return_order_counts() {
+        assert!(orders_result("MATCH (n) RETURN n order by n.name LIMIT 5"));
+        assert!(!orders_result("MATCH (n) RETURN n // ORDER BY n.name"));
+        assert!(!orders_result("MATCH (n) WHERE n.note = 'ORDER BY' RETURN n"));
+        assert!(!orders_result("MATCH (n) WITH n ORDER BY n.name RETURN n"));
+        assert!(!orders_result("CALL { MATCH (n) RETURN n ORDER BY n.name } RETURN n"));
+        assert!(!orders_result("MATCH (n) RETURN COLLECT { MATCH (n)--(m) RETURN m ORDER BY m.x } AS ms"));
+        assert!(!orders_result("RETURN 1 AS x UNION RETURN 2 AS x ORDER BY x"));
+    }
+
+    #[test]
+    fn result_order_names_sorted_columns() {
+        assert_eq!(
+            result_order("MATCH (n) RETURN n.name AS name, n.age AS `the age` ORDER BY name, `the age` desc LIMIT 5"),
+            Some(vec![("name".to_string(), false), ("the age".to_string(), true)])
+        );
+        assert_eq!(result_order("MATCH (n) RETURN n ORDER BY n.name"), None);
+        assert_eq!(result_order("MATCH (n) RETURN n.name AS name"), None);
+    }
+
+    #[test]
+    fn splits_scripts_on_top_level_semicolons() {
+        let script = "CREATE (:A {s: 'x;y'});\n// note; not a split\nMATCH (n:`odd;name`) RETURN n;\n\n/* ; */ ;\nRETURN \"a\\\";b\"";
+        let statements = split_statements(script);
+        let texts: Vec<&str> = statements.iter().map(|s| s.text.as_str()).collect();
+        assert_eq!(
+            texts,
+            [
+                "CREATE (:A {s: 'x;y'})",
+                "// note; not a split\nMATCH (n:`odd;name`) RETURN n",
+                "RETURN \"a\\\";b\"",
+            ]
+        );
+        let lines: Vec<usize> = statements.iter().map(|s| s.line).collect();
+        assert_eq!(lines, [1, 2, 6]);
+    }
+}
@@ -1,7 +1,7 @@
 use agcli::CommandError;
 use thiserror::Error;
 
-#[derive(Error, Debug)]
+#[derive(Error, Debug, Clone)]
 pub enum AppError {
     #[error("Connection failed: {reason}")]
     ConnectionFailed { reason: String },
@@ -29,6 +29,33 @@ pub enum AppError {
 
     #[error("Invalid parameters: {reason}")]
     InvalidParams { reason: String },
+
+    #[error("Profile not found: {name}")]
+    ProfileNotFound { name: String },
+
+    #[error("Invalid config: {reason}")]
+    ConfigInvalid { reason: String },
+
+    #[error("TLS handshake failed: {reason}")]
+    TlsHandshakeFailed { reason: String },
+
+    #[error("Invalid TLS configuration: {reason}")]
+    TlsConfigInvalid { reason: String },
+
+    #[error("Write not permitted: {reason}")]
+    WriteNotPermitted { reason: String },
+
+    #[error("Read-only mode: {reason}")]
+    ReadOnlyMode { reason: String },
+
+    #[error("Saved query not found: {name}")]
+    SavedQueryNotFound { name: String },
+
+    #[error("Unknown schema names: {names}")]
+    UnknownSchemaNames { names: String },
+
+    #[error("Merge key is not unique: {count} :{label} nodes match {key}")]
+    MergeKeyNotUnique { label: String, key: String, count: usize },
 }
 
 impl AppError {
@@ -43,6 +70,15 @@ impl AppError {
             Self::RelNotFound { .. } => "REL_NOT_FOUND",
             Self::ConnectionNotConfigured => "CONNECTION_NOT_CONFIGURED",
             Self::InvalidParams { .. } => "INVALID_PARAMS",
+            Self::ProfileNotFound { .. } => "PROFILE_NOT_FOUND",
+            Self::ConfigInvalid { .. } => "CONFIG_INVALID",
+            Self::TlsHandshakeFailed { .. } => "TLS_HANDSHAKE_FAILED",
+            Self::TlsConfigInvalid { .. } => "TLS_CONFIG_INVALID",
+            Self::WriteNotPermitted { .. } => "WRITE_NOT_PERMITTED",
+            Self::ReadOnlyMode { .. } => "READ_ONLY_MODE",
+            Self::SavedQueryNotFound { .. } => "SAVED_QUERY_NOT_FOUND",
+            Self::UnknownSchemaNames { .. } => "UNKNOWN_SCHEMA_NAMES",
+            Self::MergeKeyNotUnique { .. } => "MERGE_KEY_NOT_UNIQUE",
         }
     }
 
@@ -81,9 +117,42 @@ impl AppError {
                     .to_string()
             }
             Self::InvalidParams { .. } => {
-                "Check parameter format. --params expects a JSON object, --props expects a JSON object"
+                "Check parameter format. --params and --props expect a JSON object (or @file.json); --param takes name=value or name:type=value; typed values use {\"$date\":\"2024-01-02\"}, $datetime, $localdatetime, $time, $localtime, $duration, $point, $bytes"
+                    .to_string()
+            }
+            Self::ProfileNotFound { name } => {
+                format!("No profile named {name}. Run `lowmain profile list` to see configured profiles")
+            }
+            Self::ConfigInvalid { .. } => {
+                "Fix the JSON in ~/.config/lowmain/config.json, .lowmain.json or the saved query library (queries.json beside config.json), or run `lowmain profile list` to locate them"
+                    .to_string()
+            }
+            Self::TlsHandshakeFailed { .. } => {
+                "The server certificate was not trusted or the TLS session was refused. Pass --ca-cert=<pem> (or NEO4J_CA_CERT) with the CA that signed it, or the server's own certificate to pin a self-signed deployment"
+                    .to_string()
+            }
+            Self::TlsConfigInvalid { .. } => {
+                "Use a bolt+s:// or neo4j+s:// URI with --ca-cert=<pem>. Client certificates and --insecure-skip-verify are not supported by the bundled Bolt driver"
                     .to_string()
             }
+            Self::WriteNotPermitted { .. } => {
+                "Re-run with --write to allow changes, or run `lowmain query check` to validate the statement without executing it"
+                    .to_string()
+            }
+            Self::ReadOnlyMode { .. } => {
+                "Mutating commands are disabled by --read-only, LOWMAIN_READ_ONLY or the profile's read_only setting. Use read commands, or switch to a profile that allows writes"
+                    .to_string()
+            }
+            Self::SavedQueryNotFound { name } => {
+                format!("No saved query named {name}. Run `lowmain saved list` to see saved queries")
+            }
+            Self::UnknownSchemaNames { .. } => {
+                "Use the suggested names or run `lowmain schema` to list labels, types and property keys. Drop --check to run the query anyway"
+                    .to_string()
+            }
+            Self::MergeKeyNotUnique { label, .. } => {
+                format!("Nothing was changed. Remove the duplicate :{label} nodes (`lowmain node find --label={label}`), then add a uniqueness constraint on the key")
+            }
         }
     }
 }
@@ -97,7 +166,13 @@ impl From<AppError> for CommandError {
 /// Map a neo4rs error to an AppError by inspecting the error message.
 pub fn map_neo4j_error(err: neo4rs::Error) -> AppError {
     let msg = err.to_string();
-    if msg.contains("authentication")
+    if msg.contains("certificate")
+        || msg.contains("handshake")
+        || msg.contains("TLS")
+        || msg.contains("Invalid DNS name")
+    {
+        AppError::TlsHandshakeFailed { reason: msg }
+    } else if msg.contains("authentication")
         || msg.contains("Unauthorized")
         || msg.contains("credentials")
     {
@@ -183,6 +258,84 @@ mod tests {
         assert_eq!(e.code(), "INVALID_PARAMS");
     }
 
+    #[test]
+    fn code_profile_not_found() {
+        let e = AppError::ProfileNotFound { name: "prod".into() };
+        assert_eq!(e.code(), "PROFILE_NOT_FOUND");
+    }
+
+    #[test]
+    fn code_config_invalid() {
+        let e = AppError::ConfigInvalid {
+            reason: "bad json".into(),
+        };
+        assert_eq!(e.code(), "CONFIG_INVALID");
+    }
+
+    #[test]
+    fn code_tls_handshake_failed() {
+        let e = AppError::TlsHandshakeFailed {
+            reason: "UnknownIssuer".into(),
+        };
+        assert_eq!(e.code(), "TLS_HANDSHAKE_FAILED");
+    }
+
+    #[test]
+    fn code_tls_config_invalid() {
+        let e = AppError::TlsConfigInvalid {
+            reason: "missing file".into(),
+        };
+        assert_eq!(e.code(), "TLS_CONFIG_INVALID");
+    }
+
+    #[test]
+    fn code_write_not_permitted() {
+        let e = AppError::WriteNotPermitted {
+            reason: "DELETE".into(),
+        };
+        assert_eq!(e.code(), "WRITE_NOT_PERMITTED");
+    }
+
+    #[test]
+    fn code_read_only_mode() {
+        let e = AppError::ReadOnlyMode {
+            reason: "node create".into(),
+        };
+        assert_eq!(e.code(), "READ_ONLY_MODE");
+    }
+
+    #[test]
+    fn code_saved_query_not_found() {
+        let e = AppError::SavedQueryNotFound { name: "top".into() };
+        assert_eq!(e.code(), "SAVED_QUERY_NOT_FOUND");
+    }
+
+    #[test]
+    fn code_unknown_schema_names() {
+        let e = AppError::UnknownSchemaNames {
+            names: "label :Persn".into(),
+        };
+        assert_eq!(e.code(), "UNKNOWN_SCHEMA_NAMES");
+    }
+
+    #[test]
+    fn code_merge_key_not_unique() {
+        let e = AppError::MergeKeyNotUnique {
+            label: "Person".into(),
+            key: "{email: \"a@b.c\"}".into(),
+            count: 2,
+        };
+        assert_eq!(e.code(), "MERGE_KEY_NOT_UNIQUE");
+        assert!(e.fix().contains("--label=Person"));
+    }
+
+    #[test]
+    fn certificate_errors_map_to_tls_handshake_failed() {
+        let io = std::io::Error::other("invalid peer certificate: UnknownIssuer");
+        let e = map_neo4j_error(neo4rs::Error::IOError { detail: io });
+        assert_eq!(e.code(), "TLS_HANDSHAKE_FAILED");
+    }
+
     #[test]
     fn connection_failed_is_retryable() {
         let e = AppError::ConnectionFailed {
@@ -201,6 +354,14 @@ mod tests {
         assert!(!AppError::RelNotFound { id: "x".into() }.retryable());
         assert!(!AppError::ConnectionNotConfigured.retryable());
         assert!(!AppError::InvalidParams { reason: "x".into() }.retryable());
+        assert!(!AppError::ProfileNotFound { name: "x".into() }.retryable());
+        assert!(!AppError::ConfigInvalid { reason: "x".into() }.retryable());
+        assert!(!AppError::TlsHandshakeFailed { reason: "x".into() }.retryable());
+        assert!(!AppError::TlsConfigInvalid { reason: "x".into() }.retryable());
+        assert!(!AppError::WriteNotPermitted { reason: "x".into() }.retryable());
+        assert!(!AppError::ReadOnlyMode { reason: "x".into() }.retryable());
+        assert!(!AppError::SavedQueryNotFound { name: "x".into() }.retryable());
+        assert!(!AppError::UnknownSchemaNames { names: "x".into() }.retryable());
     }
 
     #[test]
@@ -215,6 +376,14 @@ mod tests {
             AppError::RelNotFound { id: "7".into() },
             AppError::ConnectionNotConfigured,
             AppError::InvalidParams { reason: "r".into() },
+            AppError::ProfileNotFound { name: "p".into() },
+            AppError::ConfigInvalid { reason: "r".into() },
+            AppError::TlsHandshakeFailed { reason: "r".into() },
+            AppError::TlsConfigInvalid { reason: "r".into() },
+            AppError::WriteNotPermitted { reason: "r".into() },
+            AppError::ReadOnlyMode { reason: "r".into() },
+            AppError::SavedQueryNotFound { name: "q".into() },
+            AppError::UnknownSchemaNames { names: "n".into() },
         ];
         for v in variants {
             assert!(!v.fix().is_empty(), "fix() empty for {}", v.code());
@@ -0,0 +1,449 @@
+//! Filter language for `node find --where`.
+//!
+//! ```text
+//! filter    := or
+//! or        := and (OR and)*
+//! and       := not (AND not)*
+//! not       := NOT not | '(' filter ')' | condition
+//! condition := prop (= | <> | != | < | <= | > | >=) value
+//!            | prop (STARTS WITH | ENDS WITH | CONTAINS) string
+//!            | prop IN '[' value (',' value)* ']'
+//!            | prop IS [NOT] NULL
+//! value     := 'string' | "string" | number | true | false | bare-word
+//!            | date('...') | datetime('...') | localdatetime('...')
+//!            | time('...') | localtime('...') | duration('...')
+//! ```
+//!
+//! Keywords are case-insensitive; a property that spells one is backquoted.
+//! Values are always bound as parameters, never interpolated into the Cypher.
+
+use serde_json::{Map, Value, json};
+
+use crate::error::AppError;
+use crate::params;
+
+const EXAMPLE: &str = "Example: --where=\"age >= 30 AND (name STARTS WITH 'A' OR email IS NULL)\"";
+const TYPED_FUNCTIONS: [&str; 6] = ["date", "datetime", "localdatetime", "time", "localtime", "duration"];
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    /// Unquoted word: a property, keyword or bare value.
+    Word(String),
+    /// Backquoted property name.
+    Name(String),
+    Str(String),
+    Symbol(&'static str),
+}
+
+/// Comparison of one property against a value.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Op {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+    StartsWith,
+    EndsWith,
+    Contains,
+    In,
+    IsNull,
+    IsNotNull,
+}
+
+impl Op {
+    fn cypher(self) -> &'static str {
+        match self {
+            Self::Eq => "=",
+            Self::Ne => "<>",
+            Self::Lt => "<",
+            Self::Le => "<=",
+            Self::Gt => ">",
+            Self::Ge => ">=",
+            Self::StartsWith => "STARTS WITH",
+            Self::EndsWith => "ENDS WITH",
+            Self::Contains => "CONTAINS",
+            Self::In => "IN",
+            Self::IsNull => "IS NULL",
+            Self::IsNotNull => "IS NOT NULL",
+        }
+    }
+}
+
+/// A parsed filter.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Filter {
+    And(Vec<Filter>),
+    Or(Vec<Filter>),
+    Not(Box<Filter>),
+    Condition { property: String, op: Op, value: Value },
+}
+
+fn invalid(detail: impl std::fmt::Display) -> AppError {
+    AppError::InvalidParams {
+        reason: format!("Invalid --where: {detail}. {EXAMPLE}"),
+    }
+}
+
+fn tokenize(input: &str) -> Result<Vec<Token>, AppError> {
+    let chars: Vec<char> = input.chars().collect();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+
+    while i < chars.len() {
+        let c = chars[i];
+        match c {
+            c if c.is_whitespace() => i += 1,
+            '\'' | '"' | '`' => {
+                let mut text = String::new();
+                i += 1;
+                loop {
+                    match chars.get(i) {
+                        None => return Err(invalid(format!("unterminated {c}"))),
+                        Some('\\') if c != '`' => {
+                            text.extend(chars.get(i + 1));
+                            i += 2;
+                        }
+                        Some(&q) if q == c => {
+                            i += 1;
+                            break;
+                        }
+                        Some(&other) => {
+                            text.push(other);
+                            i += 1;
+                        }
+                    }
+                }
+                tokens.push(if c == '`' { Token::Name(text) } else { Token::Str(text) });
+            }
+            '(' | ')' | '[' | ']' | ',' | '=' => {
+                tokens.push(Token::Symbol(match c {
+                    '(' => "(",
+                    ')' => ")",
+                    '[' => "[",
+                    ']' => "]",
+                    ',' => ",",
+                    _ => "=",
+                }));
+                i += 1;
+            }
+            '<' | '>' | '!' => {
+                let next = chars.get(i + 1).copied();
+                let (symbol, width) = match (c, next) {
+                    ('<', Some('=')) => ("<=", 2),
+                    ('<', Some('>')) => ("<>", 2),
+                    ('>', Some('=')) => (">=", 2),
+                    ('!', Some('=')) => ("<>", 2),
+                    ('<', _) => ("<", 1),
+                    ('>', _) => (">", 1),
+                    _ => return Err(invalid("unexpected !")),
+                };
+                tokens.push(Token::Symbol(symbol));
+                i += width;
+            }
+            _ => {
+                let start = i;
+                while i < chars.len() && !chars[i].is_whitespace() && !"()[],=<>!'\"`".contains(chars[i]) {
+                    i += 1;
+                }
+                tokens.push(Token::Word(chars[start..i].iter().collect()));
+            }
+        }
+    }
+    Ok(tokens)
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos)
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.pos).cloned();
+        self.pos += 1;
+        token
+    }
+
+    fn keyword(&mut self, word: &str) -> bool {
+        if matches!(self.peek(), Some(Token::Word(w)) if w.eq_ignore_ascii_case(word)) {
+            self.pos += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn symbol(&mut self, symbol: &'static str) -> bool {
+        if self.peek() == Some(&Token::Symbol(symbol)) {
+            self.pos += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expect_keyword(&mut self, word: &str) -> Result<(), AppError> {
+        if self.keyword(word) {
+            Ok(())
+        } else {
+            Err(invalid(format!("expected {word}")))
+        }
+    }
+
+    fn or(&mut self) -> Result<Filter, AppError> {
+        let mut parts = vec![self.and()?];
+        while self.keyword("OR") {
+            parts.push(self.and()?);
+        }
+        Ok(if parts.len() == 1 { parts.remove(0) } else { Filter::Or(parts) })
+    }
+
+    fn and(&mut self) -> Result<Filter, AppError> {
+        let mut parts = vec![self.not()?];
+        while self.keyword("AND") {
+            parts.push(self.not()?);
+        }
+        Ok(if parts.len() == 1 { parts.remove(0) } else { Filter::And(parts) })
+    }
+
+    fn not(&mut self) -> Result<Filter, AppError> {
+        if self.keyword("NOT") {
+            return Ok(Filter::Not(Box::new(self.not()?)));
+        }
+        if self.symbol("(") {
+            let inner = self.or()?;
+            if !self.symbol(")") {
+                return Err(invalid("missing )"));
+            }
+            return Ok(inner);
+        }
+        self.condition()
+    }
+
+    fn condition(&mut self) -> Result<Filter, AppError> {
+        let property = match self.next() {
+            Some(Token::Word(w) | Token::Name(w)) => w,
+            Some(other) => return Err(invalid(format!("expected a property name, found {}", describe(&other)))),
+            None => return Err(invalid("expected a property name")),
+        };
+
+        let op = match self.next() {
+            Some(Token::Symbol(s)) => match s {
+                "=" => Op::Eq,
+                "<>" => Op::Ne,
+                "<" => Op::Lt,
+                "<=" => Op::Le,
+                ">" => Op::Gt,
+                ">=" => Op::Ge,
+                _ => return Err(invalid(format!("unexpected {s} after {property}"))),
+            },
+            Some(Token::Word(w)) => match w.to_uppercase().as_str() {
+                "STARTS" => {
+                    self.expect_keyword("WITH")?;
+                    Op::StartsWith
+                }
+                "ENDS" => {
+                    self.expect_keyword("WITH")?;
+                    Op::EndsWith
+                }
+                "CONTAINS" => Op::Contains,
+                "IN" => Op::In,
+                "IS" => {
+                    let negated = self.keyword("NOT");
+                    self.expect_keyword("NULL")?;
+                    let op = if negated { Op::IsNotNull } else { Op::IsNull };
+                    return Ok(Filter::Condition { property, op, value: Value::Null });
+                }
+                _ => return Err(invalid(format!("unknown operator {w} after {property}"))),
+            },
+            _ => return Err(invalid(format!("expected an operator after {property}"))),
+        };
+
+        let value = if op == Op::In {
+            self.list()?
+        } else {
+            self.value()?
+        };
+        match (op, &value) {
+            (Op::Eq | Op::Ne, Value::Null) => {
+                return Err(invalid(format!("compare with null using {property} IS NULL or IS NOT NULL")));
+            }
+            (Op::StartsWith | Op::EndsWith | Op::Contains, v) if !v.is_string() => {
+                return Err(invalid(format!("{} needs a string value", op.cypher())));
+            }
+            _ => {}
+        }
+        Ok(Filter::Condition { property, op, value })
+    }
+
+    fn list(&mut self) -> Result<Value, AppError> {
+        if !self.symbol("[") {
+            return Err(invalid("IN needs a list such as ['a', 'b']"));
+        }
+        let mut items = Vec::new();
+        if self.symbol("]") {
+            return Ok(Value::Array(items));
+        }
+        loop {
+            items.push(self.value()?);
+            if self.symbol("]") {
+                return Ok(Value::Array(items));
+            }
+            if !self.symbol(",") {
+                return Err(invalid("expected , or ] in list"));
+            }
+        }
+    }
+
+    fn value(&mut self) -> Result<Value, AppError> {
+        match self.next() {
+            Some(Token::Str(s)) => Ok(Value::String(s)),
+            Some(Token::Word(w)) if self.peek() == Some(&Token::Symbol("(")) => {
+                let function = w.to_lowercase();
+                if !TYPED_FUNCTIONS.contains(&function.as_str()) {
+                    return Err(invalid(format!("unknown function {w}; use one of {}", TYPED_FUNCTIONS.join(", "))));
+                }
+                self.pos += 1;
+                let Some(Token::Str(text)) = self.next() else {
+                    return Err(invalid(format!("{function}() takes a quoted string")));
+                };
+                if !self.symbol(")") {
+                    return Err(invalid(format!("missing ) after {function}(")));
+                }
+                Ok(json!({ format!("${function}"): text }))
+            }
+            Some(Token::Word(w)) => Ok(params::infer_scalar(&w)),
+            Some(other) => Err(invalid(format!("expected a value, found {}", describe(&other)))),
+            None => Err(invalid("expected a value")),
+        }
+    }
+}
+
+fn describe(token: &Token) -> String {
+    match token {
+        Token::Word(w) | Token::Name(w) => w.clone(),
+        Token::Str(s) => format!("'{s}'"),
+        Token::Symbol(s) => s.to_string(),
+    }
+}
+
+/// Parse a `--where` filter.
+pub fn parse(input: &str) -> Result<Filter, AppError> {
+    let mut parser = Parser {
+        tokens: tokenize(input)?,
+        pos: 0,
+    };
+    if parser.peek().is_none() {
+        return Err(invalid("empty filter"));
+    }
+    let filter = parser.or()?;
+    match parser.peek() {
+        None => Ok(filter),
+        Some(token) => Err(invalid(format!("unexpected {}", describe(token)))),
+    }
+}
+
+impl Filter {
+    /// Cypher predicate over `var` and the parameters it references (`$f0`, `$f1`, ...).
+    pub fn compile(&self, var: &str) -> (String, Map<String, Value>) {
+        let mut params = Map::new();
+        let cypher = self.compile_into(var, &mut params);
+        (cypher, params)
+    }
+
+    fn compile_into(&self, var: &str, params: &mut Map<String, Value>) -> String {
+        let join = |parts: &[Filter], params: &mut Map<String, Value>, sep: &str| {
+            parts
+                .iter()
+                .map(|p| match p {
+                    Filter::And(_) | Filter::Or(_) => format!("({})", p.compile_into(var, params)),
+                    _ => p.compile_into(var, params),
+                })
+                .collect::<Vec<_>>()
+                .join(sep)
+        };
+        match self {
+            Self::And(parts) => join(parts, params, " AND "),
+            Self::Or(parts) => join(parts, params, " OR "),
+            Self::Not(inner) => format!("NOT ({})", inner.compile_into(var, params)),
+            Self::Condition { property, op, value } => {
+                let target = format!("{var}.`{}`", property.replace('`', "``"));
+                if matches!(op, Op::IsNull | Op::IsNotNull) {
+                    return format!("{target} {}", op.cypher());
+                }
+                let name = format!("f{}", params.len());
+                params.insert(name.clone(), value.clone());
+                format!("{target} {} ${name}", op.cypher())
+            }
+        }
+    }
+
+    /// Properties the filter tests, in order of appearance.
+    pub fn properties(&self) -> Vec<&str> {
+        let mut out = Vec::new();
+        self.collect_properties(&mut out);
+        out
+    }
+
+    fn collect_properties<'a>(&'a self, out: &mut Vec<&'a str>) {
+        match self {
+            Self::And(parts) | Self::Or(parts) => parts.iter().for_each(|p| p.collect_properties(out)),
+            Self::Not(inner) => inner.collect_properties(out),
+            Self::Condition { property, .. } => {
+                if !out.contains(&property.as_str()) {
+                    out.push(property);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn compiled(input: &str) -> (String, Map<String, Value>) {
+        parse(input).unwrap().compile("n")
+    }
+
+    #[test]
+    fn compiles_typed_parameterized_conditions() {
+        let (cypher, params) = compiled("age=30");
+        assert_eq!(cypher, "n.`age` = $f0");
+        assert_eq!(params["f0"], json!(30));
+
+        let (cypher, params) = compiled(
+            "age >= 30 and (name STARTS WITH 'A' OR `order` IN [1, 2.5, \"x\"]) AND NOT email IS NULL AND born < date('2000-01-01')",
+        );
+        assert_eq!(
+            cypher,
+            "n.`age` >= $f0 AND (n.`name` STARTS WITH $f1 OR n.`order` IN $f2) AND NOT (n.`email` IS NULL) AND n.`born` < $f3"
+        );
+        assert_eq!(params["f1"], json!("A"));
+        assert_eq!(params["f2"], json!([1, 2.5, "x"]));
+        assert_eq!(params["f3"], json!({"$date": "2000-01-01"}));
+        assert_eq!(
+            parse("age > 1 OR (age < 0 AND NOT name IS NULL)").unwrap().properties(),
+            ["age", "name"]
+        );
+
+        let (cypher, params) = compiled("name = 'x\\' OR 1=1' OR active = true");
+        assert_eq!(cypher, "n.`name` = $f0 OR n.`active` = $f1");
+        assert_eq!(params["f0"], json!("x' OR 1=1"));
+        assert_eq!(params["f1"], json!(true));
+    }
+
+    #[test]
+    fn rejects_malformed_filters() {
+        for bad in ["", "age", "age >", "age = 1 AND", "(age = 1", "age ~ 1", "name CONTAINS 3", "x = null", "x IN 1", "n = foo('a')"] {
+            let err = parse(bad).unwrap_err();
+            assert_eq!(err.code(), "INVALID_PARAMS", "{bad}");
+        }
+    }
+}
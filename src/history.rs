@@ -0,0 +1,304 @@
+//! Local query history: one JSONL entry per invocation that executed Cypher.
+//!
+//! Handlers note each statement in the execution context before running it;
+//! `main` appends the entry once the envelope (and so the outcome) is known.
+//! Parameters and flags that look like secrets are redacted before writing;
+//! the Cypher text is stored as given, so a secret written as a string literal
+//! in the statement itself (rather than passed as a parameter) is recorded.
+//! Once the file passes 4 MiB it is moved aside to `<path>.1`, replacing the
+//! previous one, and entry numbers carry on from there.
+
+use agcli::{CommandRequest, ExecutionContext};
+use chrono::{DateTime, SecondsFormat, Utc};
+use serde_json::{Map, Value, json};
+use std::env;
+use std::fs::{self, OpenOptions};
+use std::io::{Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+use crate::config;
+use crate::error::AppError;
+use crate::neo4j_client;
+
+const STATEMENTS_KEY: &str = "history.statements";
+const TARGET_KEY: &str = "history.target";
+const REDACTED: &str = "[REDACTED]";
+const SECRET_WORDS: [&str; 6] = ["password", "secret", "token", "apikey", "api_key", "credential"];
+const MAX_BYTES: u64 = 4 * 1024 * 1024;
+/// Bytes read from the end of the file to find the last entry number.
+const TAIL_BYTES: u64 = 64 * 1024;
+
+/// History path: LOWMAIN_HISTORY > history.jsonl beside the user config file.
+/// `LOWMAIN_HISTORY=off` disables recording.
+pub fn path() -> Option<PathBuf> {
+    match env::var("LOWMAIN_HISTORY") {
+        Ok(v) if v == "off" => None,
+        Ok(v) => Some(PathBuf::from(v)),
+        Err(_) => config::user_config_path().map(|p| p.with_file_name("history.jsonl")),
+    }
+}
+
+/// Note a statement about to be executed, with its parameters.
+pub fn statement(
+    ctx: &mut ExecutionContext,
+    req: &CommandRequest<'_>,
+    cypher: &str,
+    params: &Value,
+) -> Result<(), AppError> {
+    if ctx.get(TARGET_KEY).is_none() {
+        let (_, db) = neo4j_client::connection_info(req)?;
+        ctx.set(TARGET_KEY, json!({ "profile": neo4j_client::profile_name(req)?, "db": db }));
+    }
+    let mut statements = match ctx.remove(STATEMENTS_KEY) {
+        Some(Value::Array(list)) => list,
+        _ => Vec::new(),
+    };
+    statements.push(json!({
+        "cypher": cypher,
+        "params": match params {
+            Value::Object(map) => redact_map(map),
+            other => other.clone(),
+        },
+    }));
+    ctx.set(STATEMENTS_KEY, statements);
+    Ok(())
+}
+
+/// Append the entry for this invocation, if it executed anything. History is
+/// best effort: a write failure never changes the command's outcome.
+pub fn append(ctx: &ExecutionContext, argv: &[String], envelope: &Value, elapsed: Duration) {
+    let (Some(path), Some(statements)) = (path(), ctx.get(STATEMENTS_KEY)) else {
+        return;
+    };
+    let target = ctx.get(TARGET_KEY).cloned().unwrap_or(Value::Null);
+    let rows = row_count(&envelope["result"]);
+
+    if let Some(parent) = path.parent() {
+        let _ = fs::create_dir_all(parent);
+    }
+    // The lock serializes numbering, rotation and the append between concurrent runs.
+    let Ok(lock) = OpenOptions::new().create(true).write(true).truncate(false).open(sibling(&path, "lock")) else {
+        return;
+    };
+    if lock.lock().is_err() {
+        return;
+    }
+    let previous = sibling(&path, "1");
+    let n = last_number(&path).or_else(|| last_number(&previous)).unwrap_or(0) + 1;
+    if fs::metadata(&path).is_ok_and(|m| m.len() >= MAX_BYTES) {
+        let _ = fs::rename(&path, &previous);
+    }
+
+    let entry = json!({
+        "n": n,
+        "ts": DateTime::<Utc>::from(SystemTime::now()).to_rfc3339_opts(SecondsFormat::Millis, true),
+        "argv": redact_args(argv),
+        "profile": target["profile"],
+        "db": target["db"],
+        "statements": statements,
+        "duration_ms": u64::try_from(elapsed.as_millis()).unwrap_or(u64::MAX),
+        "ok": envelope["ok"],
+        "code": envelope["error"]["code"],
+        "rows": rows,
+    });
+
+    if let Ok(mut file) = OpenOptions::new().create(true).append(true).open(&path) {
+        let _ = writeln!(file, "{entry}");
+    }
+}
+
+/// Rows a command returned, from the `count` or `row_count` its output reports
+/// (queries, `--stream` trailers, `saved run`), the length of `rows`, or the sum
+/// over the `statements` of a `--file` run; `None` for outputs without a row
+/// set, such as `saved run --write`.
+fn row_count(result: &Value) -> Option<u64> {
+    ["count", "row_count"]
+        .iter()
+        .find_map(|k| result[k].as_u64())
+        .or_else(|| result["rows"].as_array().map(|r| r.len() as u64))
+        .or_else(|| result["statements"].as_array()?.iter().map(row_count).sum())
+}
+
+/// `<path>.<suffix>`, e.g. the rotated `history.jsonl.1`.
+fn sibling(path: &Path, suffix: &str) -> PathBuf {
+    let mut name = path.as_os_str().to_owned();
+    name.push(format!(".{suffix}"));
+    PathBuf::from(name)
+}
+
+/// Number of the last readable entry, reading only the end of the file unless
+/// that entry is longer than the tail.
+fn last_number(path: &Path) -> Option<u64> {
+    let mut file = fs::File::open(path).ok()?;
+    let start = file.metadata().ok()?.len().saturating_sub(TAIL_BYTES);
+    let mut tail = Vec::new();
+    file.seek(SeekFrom::Start(start)).ok()?;
+    file.read_to_end(&mut tail).ok()?;
+    let found = String::from_utf8_lossy(&tail)
+        .lines()
+        .rev()
+        .find_map(|line| serde_json::from_str::<Value>(line).ok()?["n"].as_u64());
+    match found {
+        None if start > 0 => load_file(path).iter().rev().find_map(|e| e["n"].as_u64()),
+        found => found,
+    }
+}
+
+/// All entries, oldest first, including those rotated to `<path>.1`.
+/// Unreadable lines are skipped.
+pub fn load(path: &Path) -> Vec<Value> {
+    let mut entries = load_file(&sibling(path, "1"));
+    entries.extend(load_file(path));
+    entries
+}
+
+fn load_file(path: &Path) -> Vec<Value> {
+    fs::read_to_string(path)
+        .map(|raw| raw.lines().filter_map(|line| serde_json::from_str(line).ok()).collect())
+        .unwrap_or_default()
+}
+
+/// The entry numbered `n`.
+pub fn entry(n: usize) -> Result<Value, AppError> {
+    let path = path().ok_or(AppError::InvalidParams {
+        reason: "History is disabled (LOWMAIN_HISTORY=off)".into(),
+    })?;
+    load(&path)
+        .into_iter()
+        .find(|e| e["n"].as_u64() == Some(n as u64))
+        .ok_or(AppError::InvalidParams {
+            reason: format!("No history entry {n}. Run `lowmain history list` to see entries"),
+        })
+}
+
+fn is_secret(name: &str) -> bool {
+    let name = name.to_ascii_lowercase();
+    SECRET_WORDS.iter().any(|w| name.contains(w))
+}
+
+fn redact_map(map: &Map<String, Value>) -> Value {
+    map.iter()
+        .map(|(k, v)| {
+            let v = match v {
+                _ if is_secret(k) => json!(REDACTED),
+                Value::Object(inner) => redact_map(inner),
+                other => other.clone(),
+            };
+            (k.clone(), v)
+        })
+        .collect::<Map<_, _>>()
+        .into()
+}
+
+/// Arguments to re-run an entry. Redacted secret flags are dropped so the
+/// current environment supplies them; other redacted values cannot be replayed.
+pub fn replay_args(entry: &Value) -> Result<Vec<String>, AppError> {
+    let argv = entry["argv"].as_array().ok_or(AppError::InvalidParams {
+        reason: "History entry has no recorded command line".into(),
+    })?;
+    let mut args = Vec::with_capacity(argv.len());
+    for arg in argv.iter().filter_map(Value::as_str) {
+        if let Some((flag, value)) = arg.strip_prefix("--").and_then(|a| a.split_once('='))
+            && is_secret(flag)
+            && value == REDACTED
+        {
+            continue;
+        }
+        if arg.contains(REDACTED) {
+            return Err(AppError::InvalidParams {
+                reason: format!("Entry {} has redacted parameters ({arg}); re-run it manually with the real values", entry["n"]),
+            });
+        }
+        args.push(arg.to_string());
+    }
+    Ok(args)
+}
+
+/// Redact secret flags, secret `--param` values and secret keys of JSON flag values.
+fn redact_args(argv: &[String]) -> Vec<String> {
+    let mut out: Vec<String> = Vec::with_capacity(argv.len());
+    for arg in argv {
+        let spaced_param = out.last().is_some_and(|prev| prev == "--param");
+        let redacted = match arg.strip_prefix("--").and_then(|a| a.split_once('=')) {
+            Some(("param", value)) => format!("--param={}", redact_param(value)),
+            Some((flag, _)) if is_secret(flag) => format!("--{flag}={REDACTED}"),
+            Some((flag, value)) => match serde_json::from_str::<Value>(value) {
+                Ok(Value::Object(map)) => format!("--{flag}={}", redact_map(&map)),
+                _ => arg.clone(),
+            },
+            None if spaced_param => redact_param(arg),
+            None => arg.clone(),
+        };
+        out.push(redacted);
+    }
+    out
+}
+
+fn redact_param(raw: &str) -> String {
+    match raw.split_once('=') {
+        Some((key, _)) if is_secret(key.split(':').next().unwrap_or(key)) => format!("{key}={REDACTED}"),
+        _ => raw.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn secrets_are_redacted_from_args_and_params() {
+        let argv: Vec<String> = [
+            "query",
+            "RETURN $api_token",
+            "--password=hunter2",
+            "--param=api_token=abc",
+            "--param",
+            "db_password:string=xyz",
+            "--props={\"name\":\"Al\",\"secret\":\"s\"}",
+            "--limit=5",
+        ]
+        .into_iter()
+        .map(String::from)
+        .collect();
+
+        let redacted = redact_args(&argv);
+        assert_eq!(redacted[2], "--password=[REDACTED]");
+        assert_eq!(redacted[3], "--param=api_token=[REDACTED]");
+        assert_eq!(redacted[5], "db_password:string=[REDACTED]");
+        assert_eq!(redacted[6], "--props={\"name\":\"Al\",\"secret\":\"[REDACTED]\"}");
+        assert_eq!(redacted[7], "--limit=5");
+        assert!(!redacted.join(" ").contains("hunter2"));
+    }
+
+    #[test]
+    fn row_counts_come_from_streamed_and_saved_run_outputs() {
+        let streamed = json!({"cypher": "MATCH (n) RETURN n", "streamed": true, "count": 3, "truncated": false});
+        let saved_run = json!({"name": "q", "params": {}, "rows": [{"a": 1}, {"a": 2}], "count": 2});
+        let explained = json!({"rows": [{"a": 1}], "row_count": 40});
+        let written = json!({"name": "q", "executed": true, "mode": "write"});
+        assert_eq!(row_count(&streamed), Some(3));
+        assert_eq!(row_count(&saved_run), Some(2));
+        assert_eq!(row_count(&explained), Some(40));
+        assert_eq!(row_count(&json!({"rows": [1, 2]})), Some(2));
+        let script = json!({"file": "s.cypher", "statements": [explained, {"rows": [], "row_count": 0}]});
+        assert_eq!(row_count(&script), Some(40));
+        assert_eq!(row_count(&written), None);
+    }
+
+    #[test]
+    fn numbering_follows_the_last_entry_across_rotation() {
+        let path = env::temp_dir().join(format!("lowmain-history-{}.jsonl", std::process::id()));
+        let previous = sibling(&path, "1");
+        fs::write(&previous, "{\"n\":7}\n").unwrap();
+        assert_eq!(last_number(&path), None);
+        assert_eq!(last_number(&previous), Some(7));
+
+        fs::write(&path, "{\"n\":8}\nnot json\n{\"n\":9}\n{\"n\":").unwrap();
+        assert_eq!(last_number(&path), Some(9));
+        assert_eq!(load(&path).iter().map(|e| e["n"].as_u64().unwrap()).collect::<Vec<_>>(), [7, 8, 9]);
+
+        let _ = fs::remove_file(&path);
+        let _ = fs::remove_file(&previous);
+    }
+}
@@ -0,0 +1,221 @@
+//! Saved query library: named, parameterized Cypher kept beside the user config.
+
+use agcli::{ActionParam, NextAction};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::collections::BTreeMap;
+use std::env;
+use std::path::PathBuf;
+
+use crate::config;
+use crate::cypher::{self, Token};
+use crate::error::AppError;
+use crate::params;
+
+/// On-disk layout of the library file.
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+pub struct Library {
+    #[serde(default)]
+    pub queries: BTreeMap<String, SavedQuery>,
+}
+
+/// A named query and the parameters it expects.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SavedQuery {
+    pub cypher: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[serde(default)]
+    pub params: Vec<ParamSpec>,
+}
+
+/// A declared query parameter.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ParamSpec {
+    pub name: String,
+    #[serde(rename = "type", default = "any_type")]
+    pub ty: String,
+    #[serde(default = "required_by_default")]
+    pub required: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub default: Option<Value>,
+}
+
+fn any_type() -> String {
+    "any".into()
+}
+
+fn required_by_default() -> bool {
+    true
+}
+
+/// Library path: LOWMAIN_QUERIES > queries.json beside the user config file.
+pub fn path() -> Result<PathBuf, AppError> {
+    if let Ok(path) = env::var("LOWMAIN_QUERIES") {
+        return Ok(PathBuf::from(path));
+    }
+    config::user_config_path()
+        .map(|p| p.with_file_name("queries.json"))
+        .ok_or(AppError::ConfigInvalid {
+            reason: "Cannot locate the saved query library: set HOME, XDG_CONFIG_HOME or LOWMAIN_QUERIES".into(),
+        })
+}
+
+pub fn load() -> Result<(PathBuf, Library), AppError> {
+    let path = path()?;
+    let library = config::load_json(&path)?;
+    Ok((path, library))
+}
+
+impl Library {
+    pub fn get(&self, name: &str) -> Result<&SavedQuery, AppError> {
+        self.queries.get(name).ok_or(AppError::SavedQueryNotFound {
+            name: name.to_string(),
+        })
+    }
+}
+
+impl SavedQuery {
+    /// Build a saved query from explicit specs, declaring any other `$param` the
+    /// Cypher references as a required parameter of type `any`.
+    pub fn new(cypher: &str, description: Option<String>, mut specs: Vec<ParamSpec>) -> Result<Self, AppError> {
+        for spec in &specs {
+            if !params::PARAM_TYPES.contains(&spec.ty.as_str()) {
+                return Err(AppError::InvalidParams {
+                    reason: format!(
+                        "Unknown type {} for parameter {}. Use one of: {}",
+                        spec.ty,
+                        spec.name,
+                        params::PARAM_TYPES.join(", ")
+                    ),
+                });
+            }
+        }
+        for token in cypher::tokenize(cypher) {
+            if let Token::Param(name) = token
+                && !specs.iter().any(|s| s.name == name)
+            {
+                specs.push(ParamSpec {
+                    name,
+                    ty: any_type(),
+                    required: true,
+                    description: None,
+                    default: None,
+                });
+            }
+        }
+        Ok(Self {
+            cypher: cypher.to_string(),
+            description,
+            params: specs,
+        })
+    }
+
+    fn spec(&self, name: &str) -> Option<&ParamSpec> {
+        self.params.iter().find(|s| s.name == name)
+    }
+
+    /// Check supplied values against the declared parameters and fill in defaults.
+    pub fn resolve(&self, mut supplied: Map<String, Value>) -> Result<Map<String, Value>, AppError> {
+        let mut problems = Vec::new();
+        for name in supplied.keys() {
+            if self.spec(name).is_none() {
+                problems.push(format!("{name} is not declared"));
+            }
+        }
+        for spec in &self.params {
+            match supplied.get(&spec.name) {
+                Some(value) if !params::value_matches(&spec.ty, value) => {
+                    problems.push(format!("{} must be {}", spec.name, spec.ty));
+                }
+                Some(_) => {}
+                None => match &spec.default {
+                    Some(default) => {
+                        supplied.insert(spec.name.clone(), default.clone());
+                    }
+                    None if spec.required => problems.push(format!("{} is required", spec.name)),
+                    None => {
+                        supplied.insert(spec.name.clone(), Value::Null);
+                    }
+                },
+            }
+        }
+        if problems.is_empty() {
+            Ok(supplied)
+        } else {
+            Err(AppError::InvalidParams {
+                reason: problems.join("; "),
+            })
+        }
+    }
+
+    /// Declared type of a parameter, used to parse untyped `--param` values.
+    pub fn declared_type(&self, name: &str) -> Option<String> {
+        self.spec(name).map(|s| s.ty.clone())
+    }
+
+    /// `saved run` suggestion with one ActionParam per declared parameter.
+    pub fn run_action(&self, name: &str) -> NextAction {
+        let description = self.description.clone().unwrap_or_else(|| format!("Run saved query {name}"));
+        let mut action = NextAction::new(format!("lowmain saved run {name}"), description);
+        for spec in &self.params {
+            let about = match &spec.description {
+                Some(d) => format!("{} ({}): {d}", spec.name, spec.ty),
+                None => format!("{} ({})", spec.name, spec.ty),
+            };
+            let mut param = ActionParam::new().description(about).required(spec.required && spec.default.is_none());
+            if let Some(default) = &spec.default {
+                param = param.value(default.clone());
+            }
+            action = action.with_param(format!("--param {}", spec.name), param);
+        }
+        action
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn saved() -> SavedQuery {
+        let specs = vec![ParamSpec {
+            name: "limit".into(),
+            ty: "int".into(),
+            required: false,
+            description: None,
+            default: Some(json!(10)),
+        }];
+        SavedQuery::new("MATCH (p:Person {name: $name}) RETURN p LIMIT $limit", None, specs).unwrap()
+    }
+
+    #[test]
+    fn undeclared_cypher_params_are_required() {
+        let q = saved();
+        assert_eq!(q.params.len(), 2);
+        assert_eq!(q.params[1].name, "name");
+        assert!(q.params[1].required);
+    }
+
+    #[test]
+    fn resolve_validates_and_fills_defaults() {
+        let q = saved();
+        let mut supplied = Map::new();
+        supplied.insert("name".into(), json!("Alice"));
+        let resolved = q.resolve(supplied).unwrap();
+        assert_eq!(resolved["limit"], json!(10));
+
+        let err = q.resolve(Map::new()).unwrap_err();
+        assert!(err.to_string().contains("name is required"));
+
+        let mut wrong = Map::new();
+        wrong.insert("name".into(), json!("Alice"));
+        wrong.insert("limit".into(), json!("ten"));
+        wrong.insert("nmae".into(), json!("typo"));
+        let err = q.resolve(wrong).unwrap_err().to_string();
+        assert!(err.contains("limit must be int"));
+        assert!(err.contains("nmae is not declared"));
+    }
+}
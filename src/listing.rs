@@ -0,0 +1,204 @@
+//! Sorting and property projection for `node find` and `rel find`.
+//!
+//! Sort keys are backquoted into the ORDER BY of the Cypher the command builds,
+//! and their values for the last row are returned for the next page's cursor.
+//! Projections are part of its RETURN clause: only the selected properties are
+//! sent back, next to explicitly listed envelope fields, and are then converted
+//! exactly as they are without `--props`.
+
+use agcli::CommandRequest;
+use serde_json::Value;
+
+use crate::error::AppError;
+use crate::paging::SortTerm;
+
+/// One `--order-by` key.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SortKey {
+    pub property: String,
+    pub descending: bool,
+}
+
+fn names(raw: &str) -> Vec<String> {
+    raw.split(',').map(str::trim).filter(|s| !s.is_empty()).map(String::from).collect()
+}
+
+/// Parse `--order-by=prop[:asc|desc],...`.
+pub fn order_by(req: &CommandRequest<'_>) -> Result<Vec<SortKey>, AppError> {
+    let Some(raw) = req.flag("order-by") else {
+        return Ok(Vec::new());
+    };
+    let keys = names(raw)
+        .into_iter()
+        .map(|key| {
+            let (property, direction) = key.rsplit_once(':').unwrap_or((key.as_str(), "asc"));
+            let descending = match direction.to_ascii_lowercase().as_str() {
+                "asc" => false,
+                "desc" => true,
+                other => {
+                    return Err(AppError::InvalidParams {
+                        reason: format!("Invalid --order-by direction {other} for {property}. Use prop:asc or prop:desc"),
+                    });
+                }
+            };
+            Ok(SortKey {
+                property: property.to_string(),
+                descending,
+            })
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    if keys.is_empty() {
+        return Err(AppError::InvalidParams {
+            reason: "Empty --order-by. Example: --order-by=name,age:desc".into(),
+        });
+    }
+    Ok(keys)
+}
+
+/// Sort terms over `var`, ending with the internal ID so every row has a
+/// unique key and pages resume exactly after the last one.
+pub fn sort_terms(var: &str, keys: &[SortKey]) -> Vec<SortTerm> {
+    keys.iter()
+        .map(|k| SortTerm {
+            expr: format!("{var}.`{}`", k.property.replace('`', "``")),
+            descending: k.descending,
+        })
+        .chain([SortTerm {
+            expr: format!("id({var})"),
+            descending: false,
+        }])
+        .collect()
+}
+
+/// ORDER BY clause for [`sort_terms`].
+pub fn order_clause(terms: &[SortTerm]) -> String {
+    let terms: Vec<String> = terms
+        .iter()
+        .map(|t| format!("{}{}", t.expr, if t.descending { " DESC" } else { "" }))
+        .collect();
+    format!("ORDER BY {}", terms.join(", "))
+}
+
+/// RETURN item holding the values of `terms`, read back as the cursor position.
+pub fn sort_key_item(terms: &[SortTerm]) -> String {
+    let exprs: Vec<&str> = terms.iter().map(|t| t.expr.as_str()).collect();
+    format!("[{}] AS {SORT_KEY_COLUMN}", exprs.join(", "))
+}
+
+/// Column of [`sort_key_item`].
+pub const SORT_KEY_COLUMN: &str = "sort_key";
+
+/// Properties to return, from `--props` and `--exclude-props`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Projection {
+    Only(Vec<String>),
+    Except(Vec<String>),
+}
+
+/// Parse `--props=a,b` and `--exclude-props=c,d`; `None` returns every property.
+pub fn projection(req: &CommandRequest<'_>) -> Option<Projection> {
+    let exclude = req.flag("exclude-props").map(names).unwrap_or_default();
+    match req.flag("props").map(names) {
+        Some(only) => Some(Projection::Only(only.into_iter().filter(|p| !exclude.contains(p)).collect())),
+        None if exclude.is_empty() => None,
+        None => Some(Projection::Except(exclude)),
+    }
+}
+
+/// What a projected RETURN item describes, for its envelope fields.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Entity {
+    Node,
+    Relationship,
+}
+
+/// Column a projected entity is returned under. It must not shadow the
+/// matched variable, which ORDER BY still refers to.
+pub const PROJECTED_COLUMN: &str = "projected";
+
+impl Projection {
+    /// RETURN item for `var`: its envelope fields and the selected properties
+    /// as `[key, value]` pairs. Properties are selected in Cypher, so the rest
+    /// never leave the server. Binds `$projected` (see [`Projection::params`]).
+    pub fn return_item(&self, var: &str, entity: Entity) -> String {
+        let envelope = match entity {
+            Entity::Node => format!("_id: id({var}), _labels: labels({var})"),
+            Entity::Relationship => format!(
+                "_id: id({var}), _type: type({var}), _start_node_id: id(startNode({var})), _end_node_id: id(endNode({var}))"
+            ),
+        };
+        let negate = match self {
+            Self::Only(_) => "",
+            Self::Except(_) => "NOT ",
+        };
+        format!("{{{envelope}, _properties: [k IN keys({var}) WHERE {negate}k IN $projected | [k, {var}[k]]]}} AS {PROJECTED_COLUMN}")
+    }
+
+    /// The `$projected` parameter for [`Projection::return_item`].
+    pub fn params(&self) -> Value {
+        let (Self::Only(names) | Self::Except(names)) = self;
+        names.clone().into()
+    }
+
+    /// Flatten a converted [`PROJECTED_COLUMN`] value into the shape of an
+    /// unprojected entity: the envelope fields, then the property pairs.
+    pub fn flatten(projected: Value) -> Value {
+        let Value::Object(mut map) = projected else {
+            return projected;
+        };
+        let pairs = map.remove("_properties");
+        for pair in pairs.as_ref().and_then(Value::as_array).into_iter().flatten() {
+            if let [Value::String(key), value] = pair.as_array().map(Vec::as_slice).unwrap_or_default() {
+                map.insert(key.clone(), value.clone());
+            }
+        }
+        Value::Object(map)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn order_clause_backquotes_keys_and_breaks_ties_by_id() {
+        let keys = vec![
+            SortKey {
+                property: "name".into(),
+                descending: false,
+            },
+            SortKey {
+                property: "odd`key".into(),
+                descending: true,
+            },
+        ];
+        let terms = sort_terms("n", &keys);
+        assert_eq!(order_clause(&terms), "ORDER BY n.`name`, n.`odd``key` DESC, id(n)");
+        assert_eq!(sort_key_item(&terms), "[n.`name`, n.`odd``key`, id(n)] AS sort_key");
+        assert_eq!(order_clause(&sort_terms("r", &[])), "ORDER BY id(r)");
+    }
+
+    #[test]
+    fn projection_selects_properties_in_cypher() {
+        let only = Projection::Only(vec!["name".into()]);
+        assert_eq!(
+            only.return_item("n", Entity::Node),
+            "{_id: id(n), _labels: labels(n), _properties: [k IN keys(n) WHERE k IN $projected | [k, n[k]]]} AS projected"
+        );
+        assert_eq!(only.params(), json!(["name"]));
+        let except = Projection::Except(vec!["_secret".into()]);
+        assert!(except.return_item("r", Entity::Relationship).starts_with(
+            "{_id: id(r), _type: type(r), _start_node_id: id(startNode(r)), _end_node_id: id(endNode(r)), _properties: [k IN keys(r) WHERE NOT k IN $projected"
+        ));
+    }
+
+    #[test]
+    fn flattened_projection_matches_an_unprojected_entity() {
+        let projected = json!({ "_id": 4, "_labels": ["Person"], "_properties": [["name", "Al"], ["born", { "$date": "1990-01-02" }]] });
+        assert_eq!(
+            Projection::flatten(projected),
+            json!({ "_id": 4, "_labels": ["Person"], "name": "Al", "born": { "$date": "1990-01-02" } })
+        );
+    }
+}
@@ -1,9 +1,22 @@
+mod access;
+mod check;
 mod commands;
+mod config;
 mod convert;
+mod cypher;
 mod error;
+mod filter;
+mod history;
+mod library;
+mod listing;
+mod mutation;
 mod neo4j_client;
+mod paging;
+mod params;
+mod summary;
 
 use agcli::{AgentCli, ExecutionContext};
+use std::time::Instant;
 
 #[cfg(feature = "jemalloc")]
 #[global_allocator]
@@ -34,10 +47,19 @@ async fn main() {
         .command(commands::query::register())
         .command(commands::schema::register())
         .command(commands::nodes::register())
-        .command(commands::rels::register());
+        .command(commands::rels::register())
+        .command(commands::profile::register())
+        .command(commands::saved::register())
+        .command(commands::history::register());
 
+    let started = Instant::now();
     let mut ctx = ExecutionContext::default();
     let run = cli.run_env_with_context(&mut ctx).await;
-    println!("{}", run.to_json());
+    let output = run.to_json();
+    if let Ok(envelope) = serde_json::from_str(&output) {
+        let argv: Vec<String> = std::env::args().skip(1).collect();
+        history::append(&ctx, &argv, &envelope, started.elapsed());
+    }
+    println!("{output}");
     std::process::exit(run.exit_code());
 }
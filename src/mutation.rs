@@ -0,0 +1,211 @@
+//! Mutations run in an explicit transaction so `--dry-run` can roll them back.
+//!
+//! A dry run executes the same statement as the real command, reads what it
+//! returned, then rolls back. The envelope reports the outcome under `would`.
+
+use agcli::{CommandRequest, NextAction};
+use neo4rs::{Graph, Query, Row, Txn};
+use serde_json::{Map, Value, json};
+
+use crate::error::{AppError, map_neo4j_error};
+use crate::summary::Summary;
+
+/// Whether the request asked for a dry run.
+pub fn dry_run(req: &CommandRequest<'_>) -> bool {
+    req.flag("dry-run").is_some()
+}
+
+/// Run a statement and return its first row, committing or (for a dry run)
+/// rolling back afterwards. Any further rows are drained before the
+/// transaction ends.
+pub async fn execute_one(
+    graph: &Graph,
+    q: Query,
+    dry_run: bool,
+    summary: &mut Summary,
+) -> Result<Option<Row>, AppError> {
+    let mut txn = graph.start_txn().await.map_err(map_neo4j_error)?;
+    let row = first_row(&mut txn, q, summary).await?;
+    finish(txn, dry_run).await?;
+    Ok(row)
+}
+
+/// Like [`execute_one`], for a statement that must return at most one row:
+/// when it returns more, the transaction is rolled back and `too_many` is
+/// called with the row count.
+pub async fn execute_single(
+    graph: &Graph,
+    q: Query,
+    dry_run: bool,
+    summary: &mut Summary,
+    too_many: impl FnOnce(usize) -> AppError,
+) -> Result<Option<Row>, AppError> {
+    let mut txn = graph.start_txn().await.map_err(map_neo4j_error)?;
+    let mut result = txn.execute(q).await.map_err(map_neo4j_error)?;
+    summary.mark_available();
+    let row = result.next(txn.handle()).await.map_err(map_neo4j_error)?;
+    let mut rows = usize::from(row.is_some());
+    while result.next(txn.handle()).await.map_err(map_neo4j_error)?.is_some() {
+        rows += 1;
+    }
+    drop(result);
+    if rows > 1 {
+        finish(txn, true).await?;
+        return Err(too_many(rows));
+    }
+    finish(txn, dry_run).await?;
+    Ok(row)
+}
+
+/// First row of a statement, draining the rest before the next one runs.
+async fn first_row(txn: &mut Txn, q: Query, summary: &mut Summary) -> Result<Option<Row>, AppError> {
+    let mut result = txn.execute(q).await.map_err(map_neo4j_error)?;
+    summary.mark_available();
+    let row = result.next(txn.handle()).await.map_err(map_neo4j_error)?;
+    while result.next(txn.handle()).await.map_err(map_neo4j_error)?.is_some() {}
+    Ok(row)
+}
+
+/// Run an arbitrary statement and roll it back, reporting up to `limit`
+/// returned rows and the net change in node and relationship counts.
+pub async fn preview(graph: &Graph, q: Query, limit: usize, summary: &mut Summary) -> Result<Value, AppError> {
+    let mut txn = graph.start_txn().await.map_err(map_neo4j_error)?;
+    let before = graph_size(&mut txn).await?;
+
+    let mut result = txn.execute(q).await.map_err(map_neo4j_error)?;
+    summary.mark_available();
+    let mut rows = Vec::new();
+    let mut row_count = 0usize;
+    while let Some(row) = result.next(txn.handle()).await.map_err(map_neo4j_error)? {
+        if rows.len() < limit {
+            rows.push(crate::convert::row_to_json(&row));
+        }
+        row_count += 1;
+    }
+
+    let after = graph_size(&mut txn).await?;
+    finish(txn, true).await?;
+
+    Ok(json!({
+        "rows": rows,
+        "row_count": row_count,
+        "truncated": row_count > rows.len(),
+        "net_changes": {
+            "nodes": after.0 - before.0,
+            "relationships": after.1 - before.1,
+        },
+    }))
+}
+
+/// Node and relationship counts as seen inside the transaction.
+async fn graph_size(txn: &mut Txn) -> Result<(i64, i64), AppError> {
+    let mut counts = [0i64; 2];
+    for (slot, cypher) in counts.iter_mut().zip([
+        "MATCH (n) RETURN count(n) AS c",
+        "MATCH ()-[r]->() RETURN count(r) AS c",
+    ]) {
+        let mut result = txn.execute(neo4rs::query(cypher)).await.map_err(map_neo4j_error)?;
+        while let Some(row) = result.next(txn.handle()).await.map_err(map_neo4j_error)? {
+            *slot = row.get("c").unwrap_or(0);
+        }
+    }
+    Ok((counts[0], counts[1]))
+}
+
+async fn finish(txn: Txn, dry_run: bool) -> Result<(), AppError> {
+    if dry_run {
+        txn.rollback().await.map_err(map_neo4j_error)
+    } else {
+        txn.commit().await.map_err(map_neo4j_error)
+    }
+}
+
+/// Result object for a mutating command. A real run returns `outcome` with the
+/// summary added; a dry run nests the outcome under `would`.
+pub fn result(dry_run: bool, outcome: Value, summary: Value) -> Value {
+    if dry_run {
+        return json!({
+            "dry_run": true,
+            "would": outcome,
+            "summary": summary,
+        });
+    }
+    let mut outcome = outcome;
+    if let Value::Object(map) = &mut outcome {
+        map.insert("summary".into(), summary);
+    }
+    outcome
+}
+
+/// Before/after diff of a node: properties added, changed and removed, and
+/// labels added and removed. Both sides are node envelopes from `convert::column_to_json`.
+pub fn node_diff(before: &Value, after: &Value) -> Value {
+    let props = |node: &Value| -> Map<String, Value> {
+        node.as_object()
+            .map(|m| m.iter().filter(|(k, _)| !k.starts_with('_')).map(|(k, v)| (k.clone(), v.clone())).collect())
+            .unwrap_or_default()
+    };
+    let labels = |node: &Value| -> Vec<Value> { node["_labels"].as_array().cloned().unwrap_or_default() };
+    let (old, new) = (props(before), props(after));
+
+    let mut added = Map::new();
+    let mut changed = Map::new();
+    for (key, value) in &new {
+        match old.get(key) {
+            None => {
+                added.insert(key.clone(), value.clone());
+            }
+            Some(previous) if previous != value => {
+                changed.insert(key.clone(), json!({ "before": previous, "after": value }));
+            }
+            Some(_) => {}
+        }
+    }
+    let removed: Map<String, Value> = old.into_iter().filter(|(k, _)| !new.contains_key(k)).collect();
+
+    let (old_labels, new_labels) = (labels(before), labels(after));
+    json!({
+        "properties": { "added": added, "changed": changed, "removed": removed },
+        "labels": {
+            "added": new_labels.iter().filter(|l| !old_labels.contains(l)).collect::<Vec<_>>(),
+            "removed": old_labels.iter().filter(|l| !new_labels.contains(l)).collect::<Vec<_>>(),
+        },
+    })
+}
+
+/// NextAction that applies a previewed change: the same command without --dry-run.
+pub fn apply_action(command: impl Into<String>) -> NextAction {
+    NextAction::new(command, "Apply this change (re-run without --dry-run)")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn dry_run_result_nests_outcome() {
+        let outcome = json!({ "deleted": true, "id": 4 });
+        let summary = json!({ "query_type": "w" });
+
+        let real = result(false, outcome.clone(), summary.clone());
+        assert_eq!(real["deleted"], true);
+        assert_eq!(real["summary"]["query_type"], "w");
+        assert!(real.get("dry_run").is_none());
+
+        let dry = result(true, outcome, summary);
+        assert_eq!(dry["dry_run"], true);
+        assert_eq!(dry["would"]["id"], 4);
+        assert_eq!(dry["summary"]["query_type"], "w");
+    }
+
+    #[test]
+    fn node_diff_reports_property_and_label_changes() {
+        let before = json!({ "_id": 1, "_labels": ["Person", "Draft"], "name": "Al", "age": 30, "tmp": true });
+        let after = json!({ "_id": 1, "_labels": ["Person", "Employee"], "name": "Al", "age": 31, "email": "a@b.c" });
+        let diff = node_diff(&before, &after);
+        assert_eq!(diff["properties"]["added"], json!({ "email": "a@b.c" }));
+        assert_eq!(diff["properties"]["changed"], json!({ "age": { "before": 30, "after": 31 } }));
+        assert_eq!(diff["properties"]["removed"], json!({ "tmp": true }));
+        assert_eq!(diff["labels"], json!({ "added": ["Employee"], "removed": ["Draft"] }));
+    }
+}
@@ -2,40 +2,117 @@ use agcli::{CommandError, CommandRequest, ExecutionContext};
 use neo4rs::Graph;
 use std::env;
 
+use crate::config::{self, Profile};
 use crate::error::AppError;
 
 const DEFAULT_URI: &str = "bolt://localhost:7687";
 const DEFAULT_USER: &str = "neo4j";
 const DEFAULT_DB: &str = "neo4j";
+const DEFAULT_LIMIT: usize = 100;
+/// Largest batch pulled per round trip. The driver allocates and fills a whole
+/// batch before returning its first record, so this bounds memory and latency.
+const MAX_FETCH_SIZE: usize = 1000;
+const ENCRYPTED_SCHEMES: [&str; 4] = ["bolt+s://", "bolt+ssc://", "neo4j+s://", "neo4j+ssc://"];
 
-/// Resolve a connection value from: CLI flag > env var > default.
-fn resolve(req: &CommandRequest<'_>, flag: &str, env_key: &str, default: Option<&str>) -> Option<String> {
-    req.flag(flag)
-        .map(String::from)
-        .or_else(|| env::var(env_key).ok())
+/// The active profile's settings, and whether it was selected by name.
+fn active_settings(req: &CommandRequest<'_>) -> Result<(Profile, bool), AppError> {
+    Ok(config::active_profile(req)?
+        .map(|a| (a.profile, a.explicit))
+        .unwrap_or_default())
+}
+
+/// Resolve a connection value from: CLI flag > profile selected by name >
+/// env var > default profile > default. Naming a profile with --profile or
+/// LOWMAIN_PROFILE is a deliberate choice, so stray NEO4J_* variables in the
+/// environment do not redirect it.
+fn resolve(
+    req: &CommandRequest<'_>,
+    flag: &str,
+    env_key: &str,
+    profile: Option<&str>,
+    explicit: bool,
+    default: Option<&str>,
+) -> Option<String> {
+    pick(req.flag(flag), env::var(env_key).ok(), profile, explicit, default)
+}
+
+fn pick(
+    flag: Option<&str>,
+    env: Option<String>,
+    profile: Option<&str>,
+    explicit: bool,
+    default: Option<&str>,
+) -> Option<String> {
+    let from_profile = profile.map(String::from);
+    flag.map(String::from)
+        .or_else(|| from_profile.clone().filter(|_| explicit))
+        .or(env)
+        .or(from_profile)
         .or_else(|| default.map(String::from))
 }
 
-/// Build a Neo4j Graph connection from CLI flags, env vars, and defaults.
-pub async fn from_request(req: &CommandRequest<'_>, _ctx: &ExecutionContext) -> Result<Graph, CommandError> {
-    let uri = resolve(req, "uri", "NEO4J_URI", Some(DEFAULT_URI))
+/// Build a Neo4j Graph connection from CLI flags, env vars, the active profile, and defaults.
+///
+/// TLS is enabled by the URI scheme (`bolt+s://`, `neo4j+s://`); `--ca-cert` adds a trusted CA.
+pub async fn from_request(req: &CommandRequest<'_>, ctx: &ExecutionContext) -> Result<Graph, CommandError> {
+    connect(req, ctx, None).await
+}
+
+/// Like [`from_request`], but pulls at most `fetch_size` records (capped at
+/// 1000) per round trip so a bounded read never makes the server produce much
+/// more than it needs.
+pub async fn from_request_with_fetch_size(
+    req: &CommandRequest<'_>,
+    ctx: &ExecutionContext,
+    fetch_size: usize,
+) -> Result<Graph, CommandError> {
+    connect(req, ctx, Some(fetch_size.clamp(1, MAX_FETCH_SIZE))).await
+}
+
+async fn connect(
+    req: &CommandRequest<'_>,
+    _ctx: &ExecutionContext,
+    fetch_size: Option<usize>,
+) -> Result<Graph, CommandError> {
+    let (profile, explicit) = active_settings(req)?;
+
+    let uri = resolve(req, "uri", "NEO4J_URI", profile.uri.as_deref(), explicit, Some(DEFAULT_URI))
         .expect("default URI always present");
-    let user = resolve(req, "user", "NEO4J_USER", Some(DEFAULT_USER))
+    let user = resolve(req, "user", "NEO4J_USER", profile.user.as_deref(), explicit, Some(DEFAULT_USER))
         .expect("default user always present");
-    let password = resolve(req, "password", "NEO4J_PASSWORD", None)
-        .ok_or(AppError::ConnectionNotConfigured)?;
-    let db = resolve(req, "db", "NEO4J_DB", Some(DEFAULT_DB))
+    // The password source is only read when it is the one that applies.
+    let password = match (req.flag("password"), explicit) {
+        (Some(pw), _) => Some(pw.to_string()),
+        (None, true) => match profile.password()? {
+            Some(pw) => Some(pw),
+            None => env::var("NEO4J_PASSWORD").ok(),
+        },
+        (None, false) => match env::var("NEO4J_PASSWORD") {
+            Ok(pw) => Some(pw),
+            Err(_) => profile.password()?,
+        },
+    }
+    .ok_or(AppError::ConnectionNotConfigured)?;
+    let db = resolve(req, "db", "NEO4J_DB", profile.db.as_deref(), explicit, Some(DEFAULT_DB))
         .expect("default db always present");
 
-    let config = neo4rs::ConfigBuilder::default()
+    let ca_cert = tls_ca_cert(req, &profile, explicit, &uri)?;
+
+    let mut builder = neo4rs::ConfigBuilder::default()
         .uri(&uri)
         .user(&user)
         .password(&password)
-        .db(db.as_str())
-        .build()
-        .map_err(|e| AppError::ConnectionFailed {
-            reason: e.to_string(),
-        })?;
+        .db(db.as_str());
+    if let Some(ca_cert) = &ca_cert {
+        builder = builder.with_client_certificate(ca_cert);
+    }
+    if let Some(fetch_size) = fetch_size {
+        builder = builder.fetch_size(fetch_size);
+    }
+
+    let config = builder.build().map_err(|e| AppError::ConnectionFailed {
+        reason: e.to_string(),
+    })?;
 
     Graph::connect(config).await.map_err(|e| {
         let err = crate::error::map_neo4j_error(e);
@@ -43,11 +120,90 @@ pub async fn from_request(req: &CommandRequest<'_>, _ctx: &ExecutionContext) ->
     })
 }
 
-/// Return the URI and database name for display (from flags/env/defaults).
-pub fn connection_info(req: &CommandRequest<'_>) -> (String, String) {
-    let uri = resolve(req, "uri", "NEO4J_URI", Some(DEFAULT_URI))
+/// Resolve and validate TLS settings, returning the CA bundle to trust (if any).
+///
+/// neo4rs 0.8 builds its own rustls config: it trusts the CA file in addition
+/// to the native roots, always verifies the server (even for `+ssc` schemes)
+/// and never presents a client certificate. Client certificates and skipping
+/// verification therefore cannot be offered, and those options are rejected
+/// instead of being silently ignored.
+fn tls_ca_cert(
+    req: &CommandRequest<'_>,
+    profile: &Profile,
+    explicit: bool,
+    uri: &str,
+) -> Result<Option<String>, AppError> {
+    for (flag, env_key) in [
+        ("client-cert", "NEO4J_CLIENT_CERT"),
+        ("client-key", "NEO4J_CLIENT_KEY"),
+        ("insecure-skip-verify", "NEO4J_INSECURE_SKIP_VERIFY"),
+    ] {
+        if resolve(req, flag, env_key, None, false, None).is_some() {
+            return Err(AppError::TlsConfigInvalid {
+                reason: format!(
+                    "--{flag} ({env_key}) is not supported: the Bolt driver (neo4rs 0.8) cannot present client certificates or skip server verification"
+                ),
+            });
+        }
+    }
+
+    let Some(ca_cert) = resolve(req, "ca-cert", "NEO4J_CA_CERT", profile.ca_cert.as_deref(), explicit, None) else {
+        return Ok(None);
+    };
+
+    if !ENCRYPTED_SCHEMES.iter().any(|s| uri.starts_with(s)) {
+        return Err(AppError::TlsConfigInvalid {
+            reason: format!("--ca-cert requires an encrypted URI scheme, got {uri}"),
+        });
+    }
+    let pem = std::fs::read_to_string(&ca_cert).map_err(|e| AppError::TlsConfigInvalid {
+        reason: format!("Cannot read CA certificate {ca_cert}: {e}"),
+    })?;
+    if !pem.contains("-----BEGIN CERTIFICATE-----") {
+        return Err(AppError::TlsConfigInvalid {
+            reason: format!("{ca_cert} contains no PEM certificate"),
+        });
+    }
+    Ok(Some(ca_cert))
+}
+
+/// Return the URI and database name for display (from flags/env/profile/defaults).
+pub fn connection_info(req: &CommandRequest<'_>) -> Result<(String, String), AppError> {
+    let (profile, explicit) = active_settings(req)?;
+    let uri = resolve(req, "uri", "NEO4J_URI", profile.uri.as_deref(), explicit, Some(DEFAULT_URI))
         .expect("default URI always present");
-    let db = resolve(req, "db", "NEO4J_DB", Some(DEFAULT_DB))
+    let db = resolve(req, "db", "NEO4J_DB", profile.db.as_deref(), explicit, Some(DEFAULT_DB))
         .expect("default db always present");
-    (uri, db)
+    Ok((uri, db))
+}
+
+/// Row limit for listing commands: --limit > profile default_limit > 100.
+pub fn limit(req: &CommandRequest<'_>) -> Result<usize, AppError> {
+    if let Some(raw) = req.flag("limit") {
+        return raw.parse().map_err(|_| AppError::InvalidParams {
+            reason: format!("Invalid --limit: {raw}. Expected a non-negative integer"),
+        });
+    }
+    Ok(active_settings(req)?.0.default_limit.unwrap_or(DEFAULT_LIMIT))
+}
+
+/// Name of the active profile, if one is selected.
+pub fn profile_name(req: &CommandRequest<'_>) -> Result<Option<String>, AppError> {
+    Ok(config::active_profile(req)?.map(|a| a.name))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn a_profile_selected_by_name_outranks_env_vars() {
+        let env = || Some("bolt://env:7687".to_string());
+        let profile = Some("bolt://profile:7687");
+        assert_eq!(pick(None, env(), profile, true, None).as_deref(), profile);
+        assert_eq!(pick(None, env(), profile, false, None), env());
+        assert_eq!(pick(Some("bolt://flag:7687"), env(), profile, true, None).as_deref(), Some("bolt://flag:7687"));
+        assert_eq!(pick(None, env(), None, true, None), env());
+        assert_eq!(pick(None, None, None, false, Some(DEFAULT_URI)).as_deref(), Some(DEFAULT_URI));
+    }
 }
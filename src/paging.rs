@@ -0,0 +1,328 @@
+use agcli::{ActionParam, CommandRequest, NextAction};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value, json};
+
+use crate::error::AppError;
+use crate::{neo4j_client, params};
+
+/// Contents of a continuation token. Callers only ever see it base64-encoded.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct Cursor {
+    /// Fingerprint of the command and filters the token was issued for.
+    #[serde(rename = "f")]
+    fingerprint: String,
+    /// Rows returned by earlier pages.
+    #[serde(rename = "o")]
+    offset: usize,
+    /// Where the last page ended, for keyset pages.
+    #[serde(rename = "k", default, skip_serializing_if = "Option::is_none")]
+    after: Option<Position>,
+}
+
+/// Sort key of the last row returned. The next page starts after it with a
+/// WHERE predicate rather than skipping the rows before it.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Position {
+    /// Values of the sort terms for the last row, in ORDER BY order.
+    #[serde(rename = "v")]
+    pub values: Vec<Value>,
+    /// Rows already returned whose sort key equals `values`. Zero when the key
+    /// is unique, e.g. because it ends with the internal ID; otherwise the next
+    /// page starts at the key and skips these rows.
+    #[serde(rename = "t", default, skip_serializing_if = "is_zero")]
+    pub ties: usize,
+}
+
+fn is_zero(n: &usize) -> bool {
+    *n == 0
+}
+
+/// Follows the sort key of rows as they are read, so a page whose key is not
+/// unique can record how many rows share the last one.
+#[derive(Debug)]
+pub struct KeyRun {
+    last: Option<Vec<Value>>,
+    ties: usize,
+}
+
+impl KeyRun {
+    /// Start at the cursor position; a page resuming there first reads the
+    /// rows tied with it again.
+    pub fn new(after: Option<&Position>) -> Self {
+        Self {
+            last: after.map(|p| p.values.clone()),
+            ties: 0,
+        }
+    }
+
+    /// Note the next row's key; `None` if it could not be read.
+    pub fn push(&mut self, key: Option<Vec<Value>>) {
+        if key.is_some() && key == self.last {
+            self.ties += 1;
+        } else {
+            self.last = key;
+            self.ties = 1;
+        }
+    }
+
+    /// Position after the last row noted.
+    pub fn position(self) -> Option<Position> {
+        self.last.map(|values| Position { values, ties: self.ties })
+    }
+}
+
+/// One ORDER BY term: a Cypher expression and its direction.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SortTerm {
+    pub expr: String,
+    pub descending: bool,
+}
+
+impl Position {
+    /// Position after a row whose sort key, a list of term values, is unique.
+    pub fn unique(key: Value) -> Option<Self> {
+        match key {
+            Value::Array(values) => Some(Self { values, ties: 0 }),
+            _ => None,
+        }
+    }
+
+    /// WHERE predicate selecting rows ordered after this position under
+    /// `terms`, binding its values as `$after_<i>` in `params`.
+    ///
+    /// Nulls sort last ascending and first descending, as in ORDER BY. A value
+    /// of a different type from the cursor's is not comparable with it, so
+    /// sort keys should hold one type per term.
+    pub fn predicate(&self, terms: &[SortTerm], params: &mut Map<String, Value>) -> Result<String, AppError> {
+        if terms.len() != self.values.len() {
+            return Err(invalid_cursor());
+        }
+        let mut equal = Vec::new();
+        let mut after = Vec::new();
+        for (i, (term, value)) in terms.iter().zip(&self.values).enumerate() {
+            let expr = &term.expr;
+            let beyond = match (value.is_null(), term.descending) {
+                (false, false) => Some(format!("{expr} > $after_{i} OR {expr} IS NULL")),
+                (false, true) => Some(format!("{expr} < $after_{i}")),
+                (true, false) => None,
+                (true, true) => Some(format!("{expr} IS NOT NULL")),
+            };
+            if let Some(beyond) = beyond {
+                after.push(equal.iter().cloned().chain([format!("({beyond})")]).collect::<Vec<_>>().join(" AND "));
+            }
+            if value.is_null() {
+                equal.push(format!("{expr} IS NULL"));
+            } else {
+                equal.push(format!("{expr} = $after_{i}"));
+                params.insert(format!("after_{i}"), value.clone());
+            }
+        }
+        if self.ties > 0 {
+            after.push(equal.join(" AND "));
+        }
+        Ok(match after.as_slice() {
+            [] => "false".to_string(),
+            _ => after.iter().map(|a| format!("({a})")).collect::<Vec<_>>().join(" OR "),
+        })
+    }
+}
+
+impl Cursor {
+    fn encode(&self) -> String {
+        let raw = serde_json::to_string(self).expect("cursor serializes");
+        params::encode_base64(raw.as_bytes())
+    }
+
+    fn decode(token: &str) -> Result<Self, AppError> {
+        params::decode_base64(token)
+            .and_then(|raw| serde_json::from_slice(&raw).ok())
+            .ok_or_else(invalid_cursor)
+    }
+}
+
+fn invalid_cursor() -> AppError {
+    AppError::InvalidParams {
+        reason: "Invalid --cursor token. Use the next_cursor value from the previous page".into(),
+    }
+}
+
+/// The page selected by --page-size/--limit, --skip and --cursor.
+#[derive(Debug, Clone)]
+pub struct Page {
+    /// Rows per page.
+    pub size: usize,
+    /// Rows to skip on this call: --skip, plus rows tied with the cursor
+    /// position that were already returned.
+    pub skip: usize,
+    /// Keyset position: only rows ordered after it belong to the page.
+    pub after: Option<Position>,
+    /// Rows that precede this page, counting earlier pages and --skip.
+    pub offset: usize,
+    fingerprint: String,
+}
+
+impl Page {
+    /// Token for the page after this one, given how many rows this page
+    /// returned and, for keyset pages, where it ended.
+    pub fn next_cursor(&self, returned: usize, after: Option<Position>) -> String {
+        Cursor {
+            fingerprint: self.fingerprint.clone(),
+            offset: self.offset + returned,
+            after,
+        }
+        .encode()
+    }
+
+    /// `page` object for the output envelope.
+    pub fn to_json(&self, has_more: bool, next_cursor: Option<&str>) -> Value {
+        json!({
+            "size": self.size,
+            "offset": self.offset,
+            "has_more": has_more,
+            "next_cursor": next_cursor,
+        })
+    }
+
+    /// NextAction that re-runs `command` with the continuation token.
+    pub fn next_action(command: impl Into<String>, cursor: &str) -> NextAction {
+        NextAction::new(command, "Fetch the next page")
+            .with_param("--cursor", ActionParam::new().description("Continuation token").value(cursor))
+    }
+}
+
+/// Read the page selection for a listing command. `scope` identifies the command
+/// and its filters; a cursor issued for a different scope is rejected so pages
+/// never silently mix results from two queries.
+pub fn page(req: &CommandRequest<'_>, scope: &[&str]) -> Result<Page, AppError> {
+    let fingerprint = fingerprint(scope);
+    let size = match req.flag("page-size") {
+        Some(v) => parse_count(v, "page-size")?,
+        None => neo4j_client::limit(req)?,
+    };
+    let skip = match req.flag("skip") {
+        Some(v) => parse_count(v, "skip")?,
+        None => 0,
+    };
+
+    let (offset, after) = match req.flag("cursor") {
+        Some(token) => {
+            let cursor = Cursor::decode(token)?;
+            if cursor.fingerprint != fingerprint {
+                return Err(AppError::InvalidParams {
+                    reason: "--cursor was issued for a different query or filter".into(),
+                });
+            }
+            (cursor.offset, cursor.after)
+        }
+        None => (0, None),
+    };
+
+    Ok(Page {
+        size,
+        skip: skip + after.as_ref().map_or(0, |a| a.ties),
+        after,
+        offset: offset + skip,
+        fingerprint,
+    })
+}
+
+fn parse_count(raw: &str, flag: &str) -> Result<usize, AppError> {
+    raw.parse().map_err(|_| AppError::InvalidParams {
+        reason: format!("Invalid --{flag}: {raw}. Expected a non-negative integer"),
+    })
+}
+
+/// Stable FNV-1a hash of the scope parts, so tokens survive rebuilds.
+fn fingerprint(parts: &[&str]) -> String {
+    let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+    for part in parts {
+        for byte in part.bytes().chain([0]) {
+            hash ^= u64::from(byte);
+            hash = hash.wrapping_mul(0x0000_0100_0000_01b3);
+        }
+    }
+    format!("{hash:016x}")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cursor_round_trips_and_stays_opaque() {
+        let cursor = Cursor {
+            fingerprint: fingerprint(&["node", "Person", ""]),
+            offset: 200,
+            after: Some(Position {
+                values: vec![json!("Al"), json!(42)],
+                ties: 0,
+            }),
+        };
+        let token = cursor.encode();
+        assert!(!token.contains("Person"));
+        assert_eq!(Cursor::decode(&token).unwrap(), cursor);
+        assert!(Cursor::decode("not a token").is_err());
+    }
+
+    #[test]
+    fn fingerprint_separates_scope_parts() {
+        assert_ne!(fingerprint(&["ab", "c"]), fingerprint(&["a", "bc"]));
+        assert_eq!(fingerprint(&["rel", "KNOWS"]), fingerprint(&["rel", "KNOWS"]));
+    }
+
+    fn term(expr: &str, descending: bool) -> SortTerm {
+        SortTerm {
+            expr: expr.into(),
+            descending,
+        }
+    }
+
+    #[test]
+    fn position_predicate_resumes_after_the_last_key() {
+        let terms = [term("n.`name`", false), term("n.`age`", true), term("id(n)", false)];
+        let mut params = Map::new();
+        let position = Position {
+            values: vec![json!("Al"), Value::Null, json!(7)],
+            ties: 0,
+        };
+        assert_eq!(
+            position.predicate(&terms, &mut params).unwrap(),
+            "((n.`name` > $after_0 OR n.`name` IS NULL)) \
+             OR (n.`name` = $after_0 AND (n.`age` IS NOT NULL)) \
+             OR (n.`name` = $after_0 AND n.`age` IS NULL AND (id(n) > $after_2 OR id(n) IS NULL))"
+        );
+        assert_eq!(Value::Object(params), json!({"after_0": "Al", "after_2": 7}));
+        assert!(position.predicate(&terms[..2], &mut Map::new()).is_err());
+    }
+
+    #[test]
+    fn key_runs_count_rows_sharing_the_last_key() {
+        let resumed = Position {
+            values: vec![json!(3)],
+            ties: 2,
+        };
+        let mut run = KeyRun::new(Some(&resumed));
+        for key in [3, 3, 3] {
+            run.push(Some(vec![json!(key)]));
+        }
+        assert_eq!(run.position().unwrap().ties, 3);
+
+        let mut run = KeyRun::new(None);
+        for key in [1, 2, 2] {
+            run.push(Some(vec![json!(key)]));
+        }
+        assert_eq!(run.position(), Some(Position { values: vec![json!(2)], ties: 2 }));
+    }
+
+    #[test]
+    fn tied_positions_include_the_key_itself() {
+        let position = Position {
+            values: vec![json!(3)],
+            ties: 2,
+        };
+        assert_eq!(
+            position.predicate(&[term("`score`", true)], &mut Map::new()).unwrap(),
+            "((`score` < $after_0)) OR (`score` = $after_0)"
+        );
+    }
+}
@@ -0,0 +1,654 @@
+use agcli::CommandRequest;
+use chrono::{DateTime, NaiveDate, NaiveDateTime, NaiveTime};
+use neo4rs::{
+    BoltBoolean, BoltBytes, BoltDuration, BoltFloat, BoltInteger, BoltList, BoltMap, BoltNull,
+    BoltPoint2D, BoltPoint3D, BoltString, BoltType, Query,
+};
+use serde_json::{Map, Value};
+
+use crate::error::AppError;
+
+/// Parse a flag value that must be a JSON object (`--params`, `--props`, `--set`).
+/// `@path` reads the object from a file.
+pub fn parse_object(raw: &str, flag: &str) -> Result<Map<String, Value>, AppError> {
+    let contents;
+    let json = match raw.strip_prefix('@') {
+        Some(path) => {
+            contents = std::fs::read_to_string(path).map_err(|e| AppError::InvalidParams {
+                reason: format!("Cannot read --{flag} file {path}: {e}"),
+            })?;
+            contents.as_str()
+        }
+        None => raw,
+    };
+    serde_json::from_str(json).map_err(|e| AppError::InvalidParams {
+        reason: format!("Invalid --{flag} JSON: {e}"),
+    })
+}
+
+/// Query parameters from `--params` merged with repeated `--param` flags,
+/// which take precedence.
+pub fn from_request(req: &CommandRequest<'_>) -> Result<Map<String, Value>, AppError> {
+    from_request_typed(req, &|_| None)
+}
+
+/// Like [`from_request`], but a `--param` without `:type` takes the type
+/// `declared` returns for its name.
+pub fn from_request_typed(
+    req: &CommandRequest<'_>,
+    declared: &dyn Fn(&str) -> Option<String>,
+) -> Result<Map<String, Value>, AppError> {
+    let mut params = match req.flag("params") {
+        Some(raw) => parse_object(raw, "params")?,
+        None => Map::new(),
+    };
+    for raw in param_values(req.invocation().raw_args()) {
+        let (name, value) = parse_param(raw, declared)?;
+        params.insert(name, value);
+    }
+    Ok(params)
+}
+
+/// Values of every `--param` flag, in order. agcli keeps only the last value of
+/// a repeated flag and reads `--param k=v` as a bare flag plus a positional, so
+/// the raw arguments are scanned instead.
+fn param_values(raw_args: &[String]) -> Vec<&str> {
+    let mut values = Vec::new();
+    let mut args = raw_args.iter();
+    while let Some(arg) = args.next() {
+        if let Some(value) = arg.strip_prefix("--param=") {
+            values.push(value);
+        } else if arg == "--param"
+            && let Some(value) = args.next()
+        {
+            values.push(value.as_str());
+        }
+    }
+    values
+}
+
+/// Positional arguments, minus the values taken by spaced `--param k=v` flags.
+pub fn positionals<'a>(req: &'a CommandRequest<'_>) -> Vec<&'a str> {
+    let raw = req.invocation().raw_args();
+    let mut taken: Vec<&str> = raw
+        .windows(2)
+        .filter(|pair| pair[0] == "--param")
+        .map(|pair| pair[1].as_str())
+        .collect();
+    req.positionals()
+        .iter()
+        .map(String::as_str)
+        .filter(|arg| match taken.iter().position(|t| t == arg) {
+            Some(i) => {
+                taken.remove(i);
+                false
+            }
+            None => true,
+        })
+        .collect()
+}
+
+/// Types accepted by `--param name:type=value`; `any` infers the type.
+pub const PARAM_TYPES: [&str; 14] = [
+    "any",
+    "string",
+    "int",
+    "float",
+    "bool",
+    "json",
+    "date",
+    "time",
+    "localtime",
+    "datetime",
+    "localdatetime",
+    "duration",
+    "point",
+    "bytes",
+];
+
+/// Parse `name=value` or `name:type=value`. Without a type, the declared one is
+/// used if any; otherwise the value is read as a boolean, null, integer or float
+/// when it looks like one, else as a string.
+fn parse_param(raw: &str, declared: &dyn Fn(&str) -> Option<String>) -> Result<(String, Value), AppError> {
+    let invalid = |detail: &str| AppError::InvalidParams {
+        reason: format!("Invalid --param {raw}: {detail}. Use name=value or name:type=value"),
+    };
+    let (key, value) = raw.split_once('=').ok_or_else(|| invalid("missing ="))?;
+    let (name, ty) = match key.split_once(':') {
+        Some((name, ty)) => (name, Some(ty.to_string())),
+        None => (key, declared(key)),
+    };
+    if name.is_empty() {
+        return Err(invalid("missing name"));
+    }
+
+    let value = match ty.as_deref() {
+        None | Some("any") => infer_scalar(value),
+        Some("string" | "str") => Value::String(value.to_string()),
+        Some("int" | "integer") => value.parse::<i64>().map(Value::from).map_err(|_| invalid("not an integer"))?,
+        Some("float") => value
+            .parse::<f64>()
+            .ok()
+            .and_then(|f| serde_json::Number::from_f64(f).map(Value::Number))
+            .ok_or_else(|| invalid("not a finite float"))?,
+        Some("bool" | "boolean") => match value {
+            "true" => Value::Bool(true),
+            "false" => Value::Bool(false),
+            _ => return Err(invalid("expected true or false")),
+        },
+        Some("json") => serde_json::from_str(value).map_err(|e| invalid(&e.to_string()))?,
+        Some("point") => {
+            let point: Value = serde_json::from_str(value).map_err(|e| invalid(&e.to_string()))?;
+            serde_json::json!({ "$point": point })
+        }
+        Some(ty) if TYPED_TAGS.contains(&format!("${ty}").as_str()) => {
+            serde_json::json!({ format!("${ty}"): value })
+        }
+        Some(ty) => {
+            return Err(invalid(&format!("unknown type {ty} ({})", PARAM_TYPES.join(", "))));
+        }
+    };
+    Ok((name.to_string(), value))
+}
+
+/// Whether a JSON parameter value fits a declared type. Temporal, point and
+/// bytes types expect the matching `$tag` literal; null fits any type.
+pub fn value_matches(ty: &str, value: &Value) -> bool {
+    match (ty, value) {
+        (_, Value::Null) | ("any" | "json", _) => true,
+        ("string", Value::String(_)) | ("bool", Value::Bool(_)) | ("float", Value::Number(_)) => true,
+        ("int", Value::Number(n)) => n.is_i64(),
+        (ty, Value::Object(map)) => map.len() == 1 && map.contains_key(&format!("${ty}")),
+        _ => false,
+    }
+}
+
+/// Read an untyped value: bool, null, integer, float, or else a string.
+pub fn infer_scalar(raw: &str) -> Value {
+    match raw {
+        "true" => Value::Bool(true),
+        "false" => Value::Bool(false),
+        "null" => Value::Null,
+        _ => raw
+            .parse::<i64>()
+            .map(Value::from)
+            .ok()
+            .or_else(|| {
+                raw.parse::<f64>()
+                    .ok()
+                    .filter(|f| f.is_finite())
+                    .and_then(serde_json::Number::from_f64)
+                    .map(Value::Number)
+            })
+            .unwrap_or_else(|| Value::String(raw.to_string())),
+    }
+}
+
+/// Tags recognised as typed literals, e.g. `{"$date": "2024-01-02"}`.
+pub const TYPED_TAGS: [&str; 9] = [
+    "$date",
+    "$time",
+    "$localtime",
+    "$datetime",
+    "$localdatetime",
+    "$duration",
+    "$point",
+    "$bytes",
+    "$float",
+];
+
+/// Convert a JSON value to a Bolt value: arrays become lists, objects become maps,
+/// null becomes Bolt null, and single-key `$tag` objects become typed literals.
+pub fn json_to_bolt(value: &Value) -> Result<BoltType, AppError> {
+    Ok(match value {
+        Value::Null => BoltType::Null(BoltNull),
+        Value::Bool(b) => BoltType::Boolean(BoltBoolean::new(*b)),
+        Value::Number(n) => {
+            if let Some(i) = n.as_i64() {
+                BoltType::Integer(BoltInteger::new(i))
+            } else if n.is_u64() {
+                return Err(AppError::InvalidParams {
+                    reason: format!("Integer {n} does not fit in a 64-bit signed Bolt integer"),
+                });
+            } else {
+                BoltType::Float(BoltFloat::new(n.as_f64().unwrap_or(f64::NAN)))
+            }
+        }
+        Value::String(s) => BoltType::String(BoltString::new(s)),
+        Value::Array(items) => {
+            let mut list = BoltList::with_capacity(items.len());
+            for item in items {
+                list.push(json_to_bolt(item)?);
+            }
+            BoltType::List(list)
+        }
+        Value::Object(map) if is_typed_literal(map) => {
+            let (tag, inner) = map.iter().next().expect("typed literal has one key");
+            typed_to_bolt(tag, inner)?
+        }
+        Value::Object(map) => {
+            let mut bolt = BoltMap::with_capacity(map.len());
+            for (key, val) in map {
+                bolt.put(BoltString::new(key), json_to_bolt(val)?);
+            }
+            BoltType::Map(bolt)
+        }
+    })
+}
+
+/// A single-key object whose key is one of [`TYPED_TAGS`]. Other `$` keys,
+/// such as `{"$ref": ".."}`, are ordinary map entries.
+fn is_typed_literal(map: &Map<String, Value>) -> bool {
+    map.len() == 1 && map.keys().all(|k| TYPED_TAGS.contains(&k.as_str()))
+}
+
+fn invalid(tag: &str, value: &Value, expected: &str) -> AppError {
+    AppError::InvalidParams {
+        reason: format!("Invalid {tag} literal {value}: expected {expected}"),
+    }
+}
+
+fn typed_to_bolt(tag: &str, value: &Value) -> Result<BoltType, AppError> {
+    if tag == "$point" {
+        return point_to_bolt(value);
+    }
+    if tag == "$duration" && value.is_object() {
+        return duration_from_parts(value);
+    }
+    let Some(s) = value.as_str() else {
+        return Err(invalid(tag, value, "a string"));
+    };
+    let bolt = match tag {
+        "$date" => NaiveDate::parse_from_str(s, "%Y-%m-%d")
+            .ok()
+            .map(BoltType::from)
+            .ok_or_else(|| invalid(tag, value, "YYYY-MM-DD"))?,
+        "$localtime" => parse_time(s)
+            .map(BoltType::from)
+            .ok_or_else(|| invalid(tag, value, "HH:MM[:SS[.fff]]"))?,
+        "$time" => {
+            let (time, offset) = split_offset(s).ok_or_else(|| invalid(tag, value, "HH:MM:SS+HH:MM"))?;
+            let dt = DateTime::parse_from_rfc3339(&format!("1970-01-01T{}{offset}", pad_seconds(time)))
+                .map_err(|_| invalid(tag, value, "HH:MM:SS+HH:MM"))?;
+            BoltType::from((dt.time(), *dt.offset()))
+        }
+        "$localdatetime" => parse_local_datetime(s)
+            .map(BoltType::from)
+            .ok_or_else(|| invalid(tag, value, "YYYY-MM-DDTHH:MM:SS"))?,
+        "$datetime" => match s.split_once('[') {
+            Some((local, zone)) => {
+                let zone = zone.strip_suffix(']').ok_or_else(|| invalid(tag, value, "a closing ]"))?;
+                let local = parse_local_datetime(local)
+                    .ok_or_else(|| invalid(tag, value, "YYYY-MM-DDTHH:MM:SS[Zone/Id]"))?;
+                BoltType::from((local, zone))
+            }
+            None => DateTime::parse_from_rfc3339(s)
+                .map(BoltType::from)
+                .map_err(|_| invalid(tag, value, "an RFC 3339 timestamp with offset"))?,
+        },
+        "$duration" => parse_duration(s).ok_or_else(|| invalid(tag, value, "ISO 8601 like P1DT2H30M"))?,
+        "$bytes" => decode_base64(s)
+            .map(|b| BoltType::Bytes(BoltBytes::new(b.into())))
+            .ok_or_else(|| invalid(tag, value, "base64"))?,
+        "$float" => s
+            .parse::<f64>()
+            .map(|f| BoltType::Float(BoltFloat::new(f)))
+            .map_err(|_| invalid(tag, value, "NaN, inf or -inf"))?,
+        _ => {
+            return Err(AppError::InvalidParams {
+                reason: format!("Unknown typed literal {tag}. Supported: {}", TYPED_TAGS.join(", ")),
+            });
+        }
+    };
+    Ok(bolt)
+}
+
+fn parse_time(s: &str) -> Option<NaiveTime> {
+    NaiveTime::parse_from_str(&pad_seconds(s), "%H:%M:%S%.f").ok()
+}
+
+fn parse_local_datetime(s: &str) -> Option<NaiveDateTime> {
+    let (date, time) = s.split_once('T')?;
+    let date = NaiveDate::parse_from_str(date, "%Y-%m-%d").ok()?;
+    Some(date.and_time(parse_time(time)?))
+}
+
+/// Accept `HH:MM` by appending `:00`.
+fn pad_seconds(time: &str) -> String {
+    if time.matches(':').count() == 1 {
+        format!("{time}:00")
+    } else {
+        time.to_string()
+    }
+}
+
+/// Split `12:00:00+01:00` / `12:00:00Z` into time and offset parts.
+fn split_offset(s: &str) -> Option<(&str, &str)> {
+    if let Some(time) = s.strip_suffix('Z') {
+        return Some((time, "Z"));
+    }
+    let idx = s.rfind(['+', '-'])?;
+    Some((&s[..idx], &s[idx..]))
+}
+
+/// Parse an ISO 8601 duration (`P1Y2M3W4DT5H6M7.5S`, optionally negative).
+fn parse_duration(s: &str) -> Option<BoltType> {
+    let (sign, rest) = match s.strip_prefix('-') {
+        Some(r) => (-1, r),
+        None => (1, s),
+    };
+    let rest = rest.strip_prefix('P')?;
+    let (date_part, time_part) = rest.split_once('T').unwrap_or((rest, ""));
+    if date_part.is_empty() && time_part.is_empty() {
+        return None;
+    }
+
+    // Out-of-range components are rejected rather than wrapped.
+    let add = |total: i64, n: i64, scale: i64| n.checked_mul(scale).and_then(|n| total.checked_add(n));
+    let (mut months, mut days, mut seconds, mut nanos) = (0i64, 0i64, 0i64, 0i64);
+    for (num, unit) in duration_components(date_part)? {
+        let n: i64 = num.parse().ok()?;
+        match unit {
+            'Y' => months = add(months, n, 12)?,
+            'M' => months = add(months, n, 1)?,
+            'W' => days = add(days, n, 7)?,
+            'D' => days = add(days, n, 1)?,
+            _ => return None,
+        }
+    }
+    for (num, unit) in duration_components(time_part)? {
+        match unit {
+            'H' => seconds = add(seconds, num.parse().ok()?, 3600)?,
+            'M' => seconds = add(seconds, num.parse().ok()?, 60)?,
+            'S' => {
+                let (whole, frac) = num.split_once('.').unwrap_or((num, ""));
+                seconds = add(seconds, whole.parse().ok()?, 1)?;
+                if !frac.is_empty() {
+                    let digits: String = frac.chars().chain(std::iter::repeat('0')).take(9).collect();
+                    // The fraction takes the sign of its component: -0.5S is minus half a second.
+                    let sign = if whole.starts_with('-') { -1 } else { 1 };
+                    nanos = sign * digits.parse::<i64>().ok()?;
+                }
+            }
+            _ => return None,
+        }
+    }
+
+    Some(BoltType::Duration(BoltDuration::new(
+        months.checked_mul(sign)?.into(),
+        days.checked_mul(sign)?.into(),
+        seconds.checked_mul(sign)?.into(),
+        (sign * nanos).into(),
+    )))
+}
+
+/// A duration in the form durations are read back in: `{"seconds", "nanos"}`
+/// totals, without separate months and days.
+fn duration_from_parts(value: &Value) -> Result<BoltType, AppError> {
+    let seconds = value.get("seconds").and_then(Value::as_i64);
+    let nanos = match value.get("nanos") {
+        None => Some(0),
+        Some(n) => n.as_i64(),
+    };
+    let (Some(seconds), Some(nanos)) = (seconds, nanos) else {
+        return Err(invalid("$duration", value, "{\"seconds\": integer, \"nanos\": integer}"));
+    };
+    Ok(BoltType::Duration(BoltDuration::new(
+        0.into(),
+        0.into(),
+        seconds.into(),
+        nanos.into(),
+    )))
+}
+
+/// Split `1Y2M` into `[("1", 'Y'), ("2", 'M')]`.
+fn duration_components(s: &str) -> Option<Vec<(&str, char)>> {
+    let mut out = Vec::new();
+    let mut start = 0;
+    for (i, c) in s.char_indices() {
+        if c.is_ascii_alphabetic() {
+            if i == start {
+                return None;
+            }
+            out.push((&s[start..i], c));
+            start = i + c.len_utf8();
+        }
+    }
+    (start == s.len()).then_some(out)
+}
+
+fn point_to_bolt(value: &Value) -> Result<BoltType, AppError> {
+    let expected = "{x, y[, z][, srid]} or {longitude, latitude[, height]}";
+    let obj = value.as_object().ok_or_else(|| invalid("$point", value, expected))?;
+    let num = |key: &str| obj.get(key).and_then(Value::as_f64);
+    let geographic = obj.contains_key("longitude");
+
+    let (x, y, z) = if geographic {
+        (num("longitude"), num("latitude"), num("height"))
+    } else {
+        (num("x"), num("y"), num("z"))
+    };
+    let (Some(x), Some(y)) = (x, y) else {
+        return Err(invalid("$point", value, expected));
+    };
+    let default_srid = match (geographic, z.is_some()) {
+        (true, false) => 4326,
+        (true, true) => 4979,
+        (false, false) => 7203,
+        (false, true) => 9157,
+    };
+    let srid = obj.get("srid").and_then(Value::as_i64).unwrap_or(default_srid);
+
+    Ok(match z {
+        None => BoltType::Point2D(BoltPoint2D {
+            sr_id: srid.into(),
+            x: BoltFloat::new(x),
+            y: BoltFloat::new(y),
+        }),
+        Some(z) => BoltType::Point3D(BoltPoint3D {
+            sr_id: srid.into(),
+            x: BoltFloat::new(x),
+            y: BoltFloat::new(y),
+            z: BoltFloat::new(z),
+        }),
+    })
+}
+
+const BASE64_ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+/// Decode standard (padded or unpadded) base64. Malformed input is rejected:
+/// padding must complete a 4-character group, a lone trailing character
+/// cannot encode a byte, and the unused bits of the last character must be 0.
+pub fn decode_base64(s: &str) -> Option<Vec<u8>> {
+    let data = s.trim_end_matches('=');
+    let padding = s.len() - data.len();
+    if padding > 2 || (padding > 0 && !s.len().is_multiple_of(4)) || data.len() % 4 == 1 {
+        return None;
+    }
+    let mut out = Vec::with_capacity(data.len() * 3 / 4);
+    let mut buf = 0u32;
+    let mut bits = 0;
+    for c in data.bytes() {
+        let v = BASE64_ALPHABET.iter().position(|&a| a == c)? as u32;
+        buf = (buf << 6) | v;
+        bits += 6;
+        if bits >= 8 {
+            bits -= 8;
+            out.push((buf >> bits) as u8);
+            buf &= (1 << bits) - 1;
+        }
+    }
+    (buf == 0).then_some(out)
+}
+
+/// Encode bytes as padded standard base64 (the `$bytes` wire form).
+pub fn encode_base64(bytes: &[u8]) -> String {
+    let mut out = String::with_capacity(bytes.len().div_ceil(3) * 4);
+    for chunk in bytes.chunks(3) {
+        let b = [chunk[0], *chunk.get(1).unwrap_or(&0), *chunk.get(2).unwrap_or(&0)];
+        let n = (u32::from(b[0]) << 16) | (u32::from(b[1]) << 8) | u32::from(b[2]);
+        for i in 0..4 {
+            if i <= chunk.len() {
+                out.push(BASE64_ALPHABET[(n >> (18 - 6 * i) & 0x3f) as usize] as char);
+            } else {
+                out.push('=');
+            }
+        }
+    }
+    out
+}
+
+/// Bind every entry of a JSON object as a query parameter.
+pub fn bind(mut q: Query, params: &Map<String, Value>) -> Result<Query, AppError> {
+    for (key, val) in params {
+        q = q.param(key, json_to_bolt(val)?);
+    }
+    Ok(q)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn scalars_map_to_native_bolt_types() {
+        assert_eq!(json_to_bolt(&json!(42)).unwrap(), BoltType::Integer(BoltInteger::new(42)));
+        assert_eq!(json_to_bolt(&json!(1.5)).unwrap(), BoltType::Float(BoltFloat::new(1.5)));
+        assert_eq!(json_to_bolt(&json!(true)).unwrap(), BoltType::Boolean(BoltBoolean::new(true)));
+        assert_eq!(json_to_bolt(&json!("a")).unwrap(), BoltType::String(BoltString::new("a")));
+        assert_eq!(json_to_bolt(&json!(null)).unwrap(), BoltType::Null(BoltNull));
+    }
+
+    #[test]
+    fn arrays_become_lists_not_strings() {
+        let bolt = json_to_bolt(&json!([1, 2, 3])).unwrap();
+        let BoltType::List(list) = bolt else {
+            panic!("expected list, got {bolt:?}");
+        };
+        assert_eq!(list.len(), 3);
+        assert_eq!(list.get(0), Some(&BoltType::Integer(BoltInteger::new(1))));
+    }
+
+    #[test]
+    fn nested_objects_become_maps() {
+        let bolt = json_to_bolt(&json!({"a": {"b": [null, "x"]}})).unwrap();
+        let BoltType::Map(outer) = bolt else {
+            panic!("expected map, got {bolt:?}");
+        };
+        let inner = outer.value.get(&BoltString::new("a")).unwrap();
+        assert!(matches!(inner, BoltType::Map(m) if m.len() == 1));
+    }
+
+    #[test]
+    fn unknown_dollar_keys_stay_maps() {
+        let bolt = json_to_bolt(&json!({"$ref": "#/definitions/a"})).unwrap();
+        let BoltType::Map(map) = bolt else {
+            panic!("expected map, got {bolt:?}");
+        };
+        assert_eq!(map.value.get(&BoltString::new("$ref")), Some(&BoltType::String(BoltString::new("#/definitions/a"))));
+    }
+
+    #[test]
+    fn typed_temporal_literals() {
+        assert!(matches!(json_to_bolt(&json!({"$date": "2024-01-02"})).unwrap(), BoltType::Date(_)));
+        assert!(matches!(json_to_bolt(&json!({"$localtime": "12:30"})).unwrap(), BoltType::LocalTime(_)));
+        assert!(matches!(json_to_bolt(&json!({"$time": "12:30:00+01:00"})).unwrap(), BoltType::Time(_)));
+        assert!(matches!(
+            json_to_bolt(&json!({"$datetime": "2024-01-02T03:04:05.5Z"})).unwrap(),
+            BoltType::DateTime(_)
+        ));
+        assert!(matches!(
+            json_to_bolt(&json!({"$datetime": "2024-01-02T03:04:05[Europe/Berlin]"})).unwrap(),
+            BoltType::DateTimeZoneId(_)
+        ));
+        assert!(matches!(
+            json_to_bolt(&json!({"$localdatetime": "2024-01-02T03:04:05"})).unwrap(),
+            BoltType::LocalDateTime(_)
+        ));
+        assert!(json_to_bolt(&json!({"$date": "02/01/2024"})).is_err());
+    }
+
+    #[test]
+    fn duration_literal_keeps_calendar_units() {
+        let bolt = json_to_bolt(&json!({"$duration": "P1Y2M3DT4H5M6.5S"})).unwrap();
+        let expected = BoltDuration::new(14.into(), 3.into(), (4 * 3600 + 5 * 60 + 6).into(), 500_000_000.into());
+        assert_eq!(bolt, BoltType::Duration(expected));
+        assert!(json_to_bolt(&json!({"$duration": "P"})).is_err());
+        assert!(json_to_bolt(&json!({"$duration": "1D"})).is_err());
+    }
+
+    #[test]
+    fn out_of_range_durations_are_rejected() {
+        for literal in ["P800000000000000000Y", "P9223372036854775807W", "PT9223372036854775807H", "-P-9223372036854775808M"] {
+            assert!(json_to_bolt(&json!({ "$duration": literal })).is_err(), "{literal}");
+        }
+        let parts = json_to_bolt(&json!({"$duration": {"seconds": 90, "nanos": 5}})).unwrap();
+        assert_eq!(parts, BoltType::Duration(BoltDuration::new(0.into(), 0.into(), 90.into(), 5.into())));
+    }
+
+    #[test]
+    fn point_literal_defaults_srid() {
+        let BoltType::Point2D(p) = json_to_bolt(&json!({"$point": {"longitude": 12.5, "latitude": 55.6}})).unwrap() else {
+            panic!("expected 2D point");
+        };
+        assert_eq!(p.sr_id.value, 4326);
+        let BoltType::Point3D(p) = json_to_bolt(&json!({"$point": {"x": 1, "y": 2, "z": 3}})).unwrap() else {
+            panic!("expected 3D point");
+        };
+        assert_eq!(p.sr_id.value, 9157);
+    }
+
+    #[test]
+    fn bytes_literal_decodes_base64() {
+        let BoltType::Bytes(b) = json_to_bolt(&json!({"$bytes": "aGVsbG8="})).unwrap() else {
+            panic!("expected bytes");
+        };
+        assert_eq!(&b.value[..], b"hello");
+        assert_eq!(encode_base64(b"hello"), "aGVsbG8=");
+        assert_eq!(encode_base64(b"hi!"), "aGkh");
+        assert_eq!(decode_base64("aGVsbG8").as_deref(), Some(&b"hello"[..]));
+    }
+
+    #[test]
+    fn malformed_base64_is_rejected() {
+        for bad in ["A", "AAAAA", "aGVsbG8==", "aGVsbG9=", "aG=VsbG8", "aGk=h", "AP8===", "="] {
+            assert_eq!(decode_base64(bad), None, "{bad}");
+        }
+    }
+
+    #[test]
+    fn oversized_unsigned_integer_is_rejected() {
+        let err = json_to_bolt(&json!(u64::MAX)).unwrap_err();
+        assert_eq!(err.code(), "INVALID_PARAMS");
+    }
+
+    #[test]
+    fn param_flags_infer_or_follow_declared_types() {
+        let untyped = |_: &str| None;
+        assert_eq!(parse_param("name=Alice", &untyped).unwrap(), ("name".into(), json!("Alice")));
+        assert_eq!(parse_param("age=30", &untyped).unwrap().1, json!(30));
+        assert_eq!(parse_param("zip:string=02139", &untyped).unwrap().1, json!("02139"));
+        assert_eq!(parse_param("age:int=30", &untyped).unwrap().1, json!(30));
+        assert_eq!(parse_param("tags:json=[\"a\"]", &untyped).unwrap().1, json!(["a"]));
+        assert_eq!(parse_param("eq=a=b", &untyped).unwrap().1, json!("a=b"));
+        assert_eq!(parse_param("born:date=1990-01-02", &untyped).unwrap().1, json!({"$date": "1990-01-02"}));
+        assert!(parse_param("age:int=thirty", &untyped).is_err());
+        assert!(parse_param("age:decimal=1", &untyped).is_err());
+        assert!(parse_param("novalue", &untyped).is_err());
+
+        let declared = |name: &str| (name == "zip").then(|| "string".to_string());
+        assert_eq!(parse_param("zip=02139", &declared).unwrap().1, json!("02139"));
+        assert!(value_matches("date", &json!({"$date": "1990-01-02"})));
+        assert!(!value_matches("int", &json!("30")));
+    }
+
+    #[test]
+    fn param_values_accept_both_spellings() {
+        let raw: Vec<String> = ["query", "RETURN $a", "--param", "a=1", "--param=b=2", "--limit=5"]
+            .into_iter()
+            .map(String::from)
+            .collect();
+        assert_eq!(param_values(&raw), ["a=1", "b=2"]);
+    }
+}
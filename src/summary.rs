@@ -0,0 +1,92 @@
+//! Result summaries attached to every command envelope.
+//!
+//! neo4rs 0.8 discards the Bolt SUCCESS metadata, so the server's update
+//! counters and notifications cannot be read; reporting them is blocked on a
+//! driver that exposes them, and lowmain does not infer them instead. The
+//! summary reports what is known client-side: the statement type as classified
+//! from its text, timing, and the server addressed.
+
+use agcli::CommandRequest;
+use serde_json::{Value, json};
+use std::time::{Duration, Instant};
+
+use crate::cypher::{self, QueryType};
+use crate::error::AppError;
+use crate::neo4j_client;
+
+/// Collects timing while a command runs.
+#[derive(Debug)]
+pub struct Summary {
+    query_type: QueryType,
+    started: Instant,
+    available_after: Option<Duration>,
+}
+
+impl Summary {
+    /// Start timing a command that runs `cypher`.
+    pub fn start(cypher: &str) -> Self {
+        Self::for_type(cypher::query_type(cypher))
+    }
+
+    /// Start timing a command whose statement type is known up front.
+    pub fn for_type(query_type: QueryType) -> Self {
+        Self {
+            query_type,
+            started: Instant::now(),
+            available_after: None,
+        }
+    }
+
+    /// Record that the first result is available. Later calls are ignored, so
+    /// multi-statement commands report the first statement's latency.
+    pub fn mark_available(&mut self) {
+        self.available_after.get_or_insert_with(|| self.started.elapsed());
+    }
+
+    /// `summary` object for the output envelope; consumption time is measured now.
+    /// The server comes from the connection settings, whose config files are
+    /// read once per invocation.
+    pub fn to_json(&self, req: &CommandRequest<'_>) -> Result<Value, AppError> {
+        let consumed_after = self.started.elapsed();
+        let (uri, db) = neo4j_client::connection_info(req)?;
+
+        Ok(json!({
+            "query_type": self.query_type.as_str(),
+            "result_available_after_ms": millis(self.available_after.unwrap_or(consumed_after)),
+            "result_consumed_after_ms": millis(consumed_after),
+            "server": {
+                "address": server_address(&uri),
+                "database": db,
+            },
+        }))
+    }
+}
+
+fn millis(d: Duration) -> u64 {
+    u64::try_from(d.as_millis()).unwrap_or(u64::MAX)
+}
+
+/// `host:port` of a Bolt URI, with the default port filled in.
+fn server_address(uri: &str) -> String {
+    let rest = uri.split_once("://").map_or(uri, |(_, rest)| rest);
+    let authority = rest.split(['/', '?']).next().unwrap_or(rest);
+    let host = authority.rsplit_once('@').map_or(authority, |(_, host)| host);
+    if host.rsplit_once(':').is_some_and(|(_, port)| port.parse::<u16>().is_ok()) {
+        host.to_string()
+    } else {
+        format!("{host}:7687")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn server_address_adds_default_port() {
+        assert_eq!(server_address("bolt://localhost:7687"), "localhost:7687");
+        assert_eq!(server_address("neo4j+s://abc.databases.neo4j.io"), "abc.databases.neo4j.io:7687");
+        assert_eq!(server_address("bolt://user@db.internal:7688/?x=1"), "db.internal:7688");
+    }
+
+}